@@ -1,13 +1,13 @@
+use advent_of_code::parse::Cursor;
+
 advent_of_code::solution!(1);
 
 /// Parse a rotation instruction like "L68" or "R14" into (direction, distance)
 fn parse_rotation(line: &str) -> Option<(char, i32)> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
-    }
-    let direction = line.chars().next()?;
-    let distance: i32 = line[1..].parse().ok()?;
+    let mut cursor = Cursor::new(line.trim());
+    let direction = cursor.next_char().ok()?;
+    let distance = cursor.unsigned().ok()?;
+    cursor.finish().ok()?;
     Some((direction, distance))
 }
 
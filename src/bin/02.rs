@@ -1,116 +1,134 @@
+use advent_of_code::parse::unsigned_ints;
+use advent_of_code::ranges::IntervalSet;
 use itertools::Itertools;
+use num_bigint::BigUint;
 
 advent_of_code::solution!(2);
 
-/// Parse a range string like "11-22" into (start, end)
-fn parse_range(s: &str) -> Option<(u64, u64)> {
-    s.trim()
-        .split('-')
-        .map(|x| x.parse::<u64>().ok())
-        .collect_tuple()
-        .and_then(|(a, b)| Some((a?, b?)))
+/// `10^exp`, or `None` past `u128::MAX`
+fn pow10(exp: u32) -> Option<u128> {
+    10u128.checked_pow(exp)
 }
 
-/// Iterator over invalid numbers (pattern repeated exactly twice) within [min, max]
-fn iter_invalid_part1(min: u64, max: u64) -> impl Iterator<Item = u64> {
-    let max_digits = if max == 0 { 1 } else { max.ilog10() as usize + 1 };
+/// The sum of all numbers in `[lo, hi]`, which must fit `u128`
+fn series_sum(lo: u128, hi: u128) -> u128 {
+    let (count, ends) = (hi - lo + 1, lo + hi);
+    if count % 2 == 0 {
+        count / 2 * ends
+    } else {
+        count * (ends / 2)
+    }
+}
 
-    (1..=max_digits / 2).flat_map(move |pattern_len| {
-        let pattern_min = if pattern_len == 1 {
-            1
-        } else {
-            10u64.pow(pattern_len as u32 - 1)
-        };
-        let pattern_max = 10u64.pow(pattern_len as u32) - 1;
-        let multiplier = 10u64.pow(pattern_len as u32);
+/// The sum of the `len`-digit numbers in `[lo, hi]` made of a `pattern_len`-digit pattern
+/// repeated, e.g. `123123` for a length of 6 and a pattern length of 3.
+///
+/// These are exactly the multiples `pattern * 1001` of the repunit `1001`, for patterns without
+/// a leading zero, so their sum is the repunit times a sum of consecutive patterns.
+fn repeated_sum(len: u32, pattern_len: u32, lo: u128, hi: u128) -> BigUint {
+    let shift = pow10(pattern_len).unwrap();
+    let repunit = (1..len / pattern_len).fold(1, |acc, _| acc * shift + 1);
 
-        (pattern_min..=pattern_max).filter_map(move |pattern| {
-            let num = pattern * multiplier + pattern;
-            if num >= min && num <= max {
-                Some(num)
-            } else {
-                None
+    let first = (shift / 10).max(lo.div_ceil(repunit));
+    let last = (shift - 1).min(hi / repunit);
+    if first > last {
+        return BigUint::ZERO;
+    }
+    BigUint::from(series_sum(first, last)) * repunit
+}
+
+/// The distinct prime factors of `n`
+fn prime_factors(mut n: u32) -> Vec<u32> {
+    let mut factors = vec![];
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
             }
-        })
-    })
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
 }
 
-/// Iterator over invalid numbers (pattern repeated at least twice) within [min, max]
-fn iter_invalid_part2(min: u64, max: u64) -> impl Iterator<Item = u64> {
-    let max_digits = if max == 0 { 1 } else { max.ilog10() as usize + 1 };
+/// The sum of the `len`-digit numbers in `[lo, hi]` made of a pattern repeated at least twice.
+///
+/// A number repeats a pattern `r` times exactly if it repeats one `q` times for each prime `q`
+/// dividing `r`, so it's enough to look at prime repetition counts. Numbers repeating patterns
+/// for several of them are counted once by inclusion–exclusion: those repeating patterns both
+/// `q1` and `q2` times are the ones repeating a pattern `q1 * q2` times.
+fn repeated_sum_any(len: u32, lo: u128, hi: u128) -> BigUint {
+    let primes = prime_factors(len);
+    let (mut added, mut removed) = (BigUint::ZERO, BigUint::ZERO);
 
-    (1..=max_digits / 2).flat_map(move |pattern_len| {
-        let pattern_min = if pattern_len == 1 {
-            1
+    for subset in (1..=primes.len()).flat_map(|k| primes.iter().combinations(k)) {
+        let reps: u32 = subset.iter().copied().product();
+        let sum = repeated_sum(len, len / reps, lo, hi);
+        if subset.len() % 2 == 1 {
+            added += sum;
         } else {
-            10u64.pow(pattern_len as u32 - 1)
-        };
-        let pattern_max = 10u64.pow(pattern_len as u32) - 1;
-        let multiplier = 10u64.pow(pattern_len as u32);
+            removed += sum;
+        }
+    }
 
-        (2..=max_digits / pattern_len).flat_map(move |reps| {
-            (pattern_min..=pattern_max).filter_map(move |pattern| {
-                let mut num = 0u64;
-                for _ in 0..reps {
-                    num = num * multiplier + pattern;
-                }
-                if num >= min && num <= max {
-                    Some(num)
-                } else {
-                    None
-                }
-            })
-        })
-    })
+    added - removed
 }
 
-/// Merge overlapping ranges to avoid duplicate counting
-fn merge_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
-    if ranges.is_empty() {
-        return Vec::new();
-    }
-
-    let mut sorted: Vec<_> = ranges.to_vec();
-    sorted.sort_by_key(|r| r.0);
+/// Split `[lo, hi]` into parts with the same number of digits, as `(digits, lo, hi)`
+fn by_digit_count(lo: u128, hi: u128) -> impl Iterator<Item = (u32, u128, u128)> {
+    let digits = |n: u128| n.checked_ilog10().unwrap_or(0) + 1;
+    (digits(lo)..=digits(hi)).map(move |len| {
+        let first = pow10(len - 1).unwrap().max(lo);
+        let last = pow10(len).map_or(u128::MAX, |p| p - 1).min(hi);
+        (len, first, last)
+    })
+}
 
-    let mut merged = Vec::with_capacity(sorted.len());
-    merged.push(sorted[0]);
+/// Sum of invalid numbers (pattern repeated exactly twice) within [min, max]
+fn sum_invalid_part1(min: u128, max: u128) -> BigUint {
+    by_digit_count(min, max)
+        .filter(|&(len, _, _)| len % 2 == 0)
+        .map(|(len, lo, hi)| repeated_sum(len, len / 2, lo, hi))
+        .sum()
+}
 
-    for &(start, end) in &sorted[1..] {
-        let last = merged.last_mut().unwrap();
-        if start <= last.1 + 1 {
-            last.1 = last.1.max(end);
-        } else {
-            merged.push((start, end));
-        }
-    }
+/// Sum of invalid numbers (pattern repeated at least twice) within [min, max]
+fn sum_invalid_part2(min: u128, max: u128) -> BigUint {
+    by_digit_count(min, max)
+        .map(|(len, lo, hi)| repeated_sum_any(len, lo, hi))
+        .sum()
+}
 
-    merged
+/// Parse the comma-separated ranges, merging overlaps to avoid duplicate counting
+fn parse_ranges(input: &str) -> IntervalSet<u128> {
+    unsigned_ints::<u128>(input)
+        .tuples()
+        .map(|(start, end)| start..=end)
+        .collect()
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let ranges: Vec<(u64, u64)> = input.split(',').filter_map(parse_range).collect();
-    let merged = merge_ranges(&ranges);
+pub fn part_one(input: &str) -> Option<BigUint> {
+    let merged = parse_ranges(input);
 
-    // Sum invalid numbers directly from iterator, checking merged ranges
-    let sum: u64 = merged
+    let sum = merged
         .iter()
-        .flat_map(|&(start, end)| iter_invalid_part1(start, end))
+        .map(|range| sum_invalid_part1(*range.start(), *range.end()))
         .sum();
 
     Some(sum)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let ranges: Vec<(u64, u64)> = input.split(',').filter_map(parse_range).collect();
-    let merged = merge_ranges(&ranges);
+pub fn part_two(input: &str) -> Option<BigUint> {
+    let merged = parse_ranges(input);
 
-    // Use itertools::unique() for deduplication
-    // (needed because same number can match multiple pattern lengths)
-    let sum: u64 = merged
+    let sum = merged
         .iter()
-        .flat_map(|&(start, end)| iter_invalid_part2(start, end))
-        .unique()
+        .map(|range| sum_invalid_part2(*range.start(), *range.end()))
         .sum();
 
     Some(sum)
@@ -120,15 +138,126 @@ pub fn part_two(input: &str) -> Option<u64> {
 mod tests {
     use super::*;
 
+    /// Iterator over invalid numbers (pattern repeated exactly twice) within [min, max]
+    fn iter_invalid_part1(min: u64, max: u64) -> impl Iterator<Item = u64> {
+        let max_digits = if max == 0 {
+            1
+        } else {
+            max.ilog10() as usize + 1
+        };
+
+        (1..=max_digits / 2).flat_map(move |pattern_len| {
+            let pattern_min = if pattern_len == 1 {
+                1
+            } else {
+                10u64.pow(pattern_len as u32 - 1)
+            };
+            let pattern_max = 10u64.pow(pattern_len as u32) - 1;
+            let multiplier = 10u64.pow(pattern_len as u32);
+
+            (pattern_min..=pattern_max).filter_map(move |pattern| {
+                let num = pattern * multiplier + pattern;
+                if num >= min && num <= max {
+                    Some(num)
+                } else {
+                    None
+                }
+            })
+        })
+    }
+
+    /// Iterator over invalid numbers (pattern repeated at least twice) within [min, max]
+    fn iter_invalid_part2(min: u64, max: u64) -> impl Iterator<Item = u64> {
+        let max_digits = if max == 0 {
+            1
+        } else {
+            max.ilog10() as usize + 1
+        };
+
+        (1..=max_digits / 2).flat_map(move |pattern_len| {
+            let pattern_min = if pattern_len == 1 {
+                1
+            } else {
+                10u64.pow(pattern_len as u32 - 1)
+            };
+            let pattern_max = 10u64.pow(pattern_len as u32) - 1;
+            let multiplier = 10u64.pow(pattern_len as u32);
+
+            (2..=max_digits / pattern_len).flat_map(move |reps| {
+                (pattern_min..=pattern_max).filter_map(move |pattern| {
+                    let mut num = 0u64;
+                    for _ in 0..reps {
+                        num = num * multiplier + pattern;
+                    }
+                    if num >= min && num <= max {
+                        Some(num)
+                    } else {
+                        None
+                    }
+                })
+            })
+        })
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(1227775554));
+        assert_eq!(result, Some(1227775554u64.into()));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(4174379265));
+        assert_eq!(result, Some(4174379265u64.into()));
+    }
+
+    #[test]
+    fn test_matches_enumeration() {
+        // Random ranges of up to 8 digits, from a fixed xorshift sequence
+        let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut next = |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        for _ in 0..300 {
+            let digits = next(8) as u32 + 1;
+            let min = next(10u64.pow(digits));
+            let width = next(digits as u64) as u32 + 1;
+            let max = min + next(10u64.pow(width));
+
+            let part1: u64 = iter_invalid_part1(min, max).sum();
+            let part2: u64 = iter_invalid_part2(min, max).unique().sum();
+            let range = format!("{min}..={max}");
+            assert_eq!(
+                sum_invalid_part1(min.into(), max.into()),
+                part1.into(),
+                "{range}"
+            );
+            assert_eq!(
+                sum_invalid_part2(min.into(), max.into()),
+                part2.into(),
+                "{range}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_huge_ranges() {
+        // `12` repeated 19 times repeats a pattern, but not exactly twice
+        let twelves: u128 = "12".repeat(19).parse().unwrap();
+        assert_eq!(sum_invalid_part1(twelves, twelves), BigUint::ZERO);
+        assert_eq!(sum_invalid_part2(twelves, twelves), twelves.into());
+
+        // The largest number repeating a pattern twice is nineteen nines twice
+        let halves: u128 = "9".repeat(19).parse().unwrap();
+        let doubled = halves * (halves + 1) + halves;
+        assert_eq!(
+            sum_invalid_part1(0, u128::MAX),
+            sum_invalid_part1(0, doubled)
+        );
+        assert!(sum_invalid_part2(0, u128::MAX) > u128::MAX.into());
     }
 }
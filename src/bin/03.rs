@@ -1,95 +1,38 @@
-use smallvec::SmallVec;
+use advent_of_code::digits::{largest_subsequence, parse_digits, to_biguint};
+use advent_of_code::template::{DayOption, day_args};
+use num_bigint::BigUint;
 
-advent_of_code::solution!(3);
+advent_of_code::solution!(3, options: DAY_OPTIONS);
 
-/// Find the maximum 2-digit number by selecting exactly 2 batteries from a bank
-fn max_joltage_2(bank: &SmallVec<[u8; 32]>) -> u64 {
-    let n = bank.len();
+const DAY_OPTIONS: &[DayOption] = &[DayOption::value::<usize>("--batteries")];
 
-    // Precompute suffix maximums: suffix_max[i] = max digit from position i to end
-    // This avoids O(n) scan for each position, making overall O(n) instead of O(n²)
-    let mut suffix_max: SmallVec<[u8; 32]> = SmallVec::from_elem(0, n);
-    suffix_max[n - 1] = bank[n - 1];
-    for i in (0..n - 1).rev() {
-        suffix_max[i] = bank[i].max(suffix_max[i + 1]);
-    }
-
-    let mut max = 0u64;
-    for i in 0..n - 1 {
-        let tens = bank[i] as u64;
-        let units = suffix_max[i + 1] as u64; // O(1) lookup instead of O(n) scan
-        let joltage = tens * 10 + units;
-        max = max.max(joltage);
-    }
-
-    max
+/// Find the maximum joltage by turning on exactly `batteries` batteries of a bank, keeping their
+/// order. `None` if the bank has fewer batteries.
+fn max_joltage(bank: &[u8], batteries: usize) -> Option<BigUint> {
+    largest_subsequence(bank, batteries).map(|digits| to_biguint(&digits))
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let mut total = 0u64;
-
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        // Convert line to digits using SmallVec (avoids heap allocation for small inputs)
-        let digits: SmallVec<[u8; 32]> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect();
-        total += max_joltage_2(&digits);
-    }
-
-    Some(total)
+/// Sum the maximum joltage of every bank, skipping banks that are malformed or too small.
+///
+/// Day options: `--batteries <k>` turns on `k` batteries per bank instead of the part's default.
+fn total_joltage(input: &str, batteries: usize) -> BigUint {
+    let batteries = day_args().value("--batteries").unwrap_or(batteries);
+
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(parse_digits)
+        .filter_map(|bank| max_joltage(&bank, batteries))
+        .sum()
 }
 
-/// Find the maximum k-digit number by selecting exactly k batteries from a bank
-fn max_joltage_k(bank: &SmallVec<[u8; 32]>, k: usize) -> u64 {
-    let n = bank.len();
-    let mut result = 0u64;
-    let mut start = 0;
-
-    // Greedily pick each of the k digits
-    for remaining in (1..=k).rev() {
-        // We can pick from [start, n - remaining] inclusive
-        let end = n - remaining;
-
-        // Find the FIRST position of the max digit in this range
-        let mut best_pos = 0;
-        let mut best_digit = bank[start];
-        for (i, &d) in bank[start..=end].iter().enumerate() {
-            if d > best_digit {
-                best_digit = d;
-                best_pos = i;
-            }
-        }
-
-        result = result * 10 + best_digit as u64;
-        start = start + best_pos + 1;
-    }
-
-    result
+pub fn part_one(input: &str) -> Option<BigUint> {
+    Some(total_joltage(input, 2))
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut total = 0u64;
-
-    for line in input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        let digits: SmallVec<[u8; 32]> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect();
-        total += max_joltage_k(&digits, 12);
-    }
-
-    Some(total)
+pub fn part_two(input: &str) -> Option<BigUint> {
+    Some(total_joltage(input, 12))
 }
 
 #[cfg(test)]
@@ -99,12 +42,35 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(357));
+        assert_eq!(result, Some(357u64.into()));
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(3121910778619));
+        assert_eq!(result, Some(3121910778619u64.into()));
+    }
+
+    #[test]
+    fn test_long_banks() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let totals: Vec<BigUint> = [1, 15]
+            .map(|batteries| total_joltage(&input, batteries))
+            .into();
+        assert_eq!(totals[0], (9 + 9 + 8 + 9u64).into());
+
+        // 15 batteries take whole banks
+        let whole: BigUint = input
+            .lines()
+            .filter_map(|line| line.parse::<BigUint>().ok())
+            .sum();
+        assert_eq!(totals[1], whole);
+
+        // Joltages beyond what fits a `u64`
+        let bank: Vec<u8> = (0..100).map(|i| (i * 7 % 10) as u8).collect();
+        let joltage = max_joltage(&bank, 30).unwrap();
+        assert_eq!(joltage.to_string().len(), 30);
+        assert!(joltage.to_string().starts_with("99999999"));
+        assert_eq!(max_joltage(&bank, 101), None);
     }
 }
@@ -1,121 +1,106 @@
+use advent_of_code::grid::{Grid, Pos};
+use advent_of_code::viz::{self, Cell, Color, Frame};
 use std::collections::VecDeque;
 
 advent_of_code::solution!(4);
 
 /// Parse input into a grid of booleans (true = paper)
-fn parse_grid(input: &str) -> Vec<Vec<bool>> {
-    input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|line| line.chars().map(|c| c == '@').collect())
-        .collect()
+fn parse_grid(input: &str) -> Grid<bool> {
+    Grid::parse(input, |c| c == '@')
 }
 
-/// Get valid neighbors for a position
-fn get_neighbors(row: usize, col: usize, rows: usize, cols: usize) -> impl Iterator<Item = (usize, usize)> {
-    const DELTAS: [(i32, i32); 8] = [
-        (-1, -1), (-1, 0), (-1, 1),
-        (0, -1),           (0, 1),
-        (1, -1),  (1, 0),  (1, 1),
-    ];
-    
-    DELTAS.into_iter().filter_map(move |(dr, dc)| {
-        let nr = row as i32 + dr;
-        let nc = col as i32 + dc;
-        if nr >= 0 && nr < rows as i32 && nc >= 0 && nc < cols as i32 {
-            Some((nr as usize, nc as usize))
-        } else {
-            None
-        }
-    })
+/// Count adjacent paper rolls for a given position
+fn count_neighbors(grid: &Grid<bool>, pos: Pos) -> u8 {
+    grid.neighbors8(pos).filter(|&next| grid[next]).count() as u8
 }
 
-/// Count adjacent paper rolls for a given position
-fn count_neighbors(grid: &[Vec<bool>], row: usize, col: usize) -> u8 {
-    let rows = grid.len();
-    let cols = grid[0].len();
-    get_neighbors(row, col, rows, cols)
-        .filter(|&(nr, nc)| grid[nr][nc])
-        .count() as u8
+/// Show the remaining paper, with the rolls queued for removal highlighted
+fn show_paper(grid: &Grid<bool>, queued: &Grid<bool>, wave: usize, removed: u64) {
+    viz::frame(|| {
+        Frame::from_grid(grid, |pos, &paper| match (paper, queued[pos]) {
+            (true, true) => Cell::new('@').fg(Color::YELLOW),
+            (true, false) => Cell::new('@').fg(Color::WHITE),
+            (false, _) => Cell::new('.').fg(Color::GRAY),
+        })
+        .caption(format!("wave {wave}: {removed} rolls removed"))
+    });
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
     let grid = parse_grid(input);
-    if grid.is_empty() {
-        return Some(0);
-    }
 
-    let mut accessible = 0u64;
-    for row in 0..grid.len() {
-        for col in 0..grid[0].len() {
-            if grid[row][col] && count_neighbors(&grid, row, col) < 4 {
-                accessible += 1;
-            }
-        }
-    }
+    let accessible = grid
+        .iter()
+        .filter(|&(pos, &paper)| paper && count_neighbors(&grid, pos) < 4)
+        .count();
 
-    Some(accessible)
+    Some(accessible as u64)
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
     let mut grid = parse_grid(input);
-    if grid.is_empty() {
-        return Some(0);
-    }
-
-    let rows = grid.len();
-    let cols = grid[0].len();
 
     // Pre-compute neighbor counts for all cells
-    let mut neighbor_counts: Vec<Vec<u8>> = vec![vec![0; cols]; rows];
-    for row in 0..rows {
-        for col in 0..cols {
-            if grid[row][col] {
-                neighbor_counts[row][col] = count_neighbors(&grid, row, col);
-            }
+    let mut neighbor_counts: Grid<u8> = Grid::filled(grid.width(), grid.height(), 0);
+    for pos in grid.positions() {
+        if grid[pos] {
+            neighbor_counts[pos] = count_neighbors(&grid, pos);
         }
     }
 
     // Initialize queue with all accessible cells (paper with < 4 neighbors)
-    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
-    let mut in_queue: Vec<Vec<bool>> = vec![vec![false; cols]; rows];
-
-    for row in 0..rows {
-        for col in 0..cols {
-            if grid[row][col] && neighbor_counts[row][col] < 4 {
-                queue.push_back((row, col));
-                in_queue[row][col] = true;
-            }
+    let mut queue: VecDeque<Pos> = VecDeque::new();
+    let mut in_queue: Grid<bool> = Grid::filled(grid.width(), grid.height(), false);
+
+    for pos in grid.positions() {
+        if grid[pos] && neighbor_counts[pos] < 4 {
+            queue.push_back(pos);
+            in_queue[pos] = true;
         }
     }
 
     let mut total_removed = 0u64;
 
-    while let Some((row, col)) = queue.pop_front() {
-        in_queue[row][col] = false;
+    // The queue holds one removal wave after the other, count them for the visualisation
+    let (mut wave, mut wave_left) = (0, queue.len());
+    show_paper(&grid, &in_queue, wave, total_removed);
+
+    while let Some(pos) = queue.pop_front() {
+        if viz::is_active() {
+            if wave_left == 0 {
+                wave += 1;
+                wave_left = queue.len() + 1;
+                show_paper(&grid, &in_queue, wave, total_removed);
+            }
+            wave_left -= 1;
+        }
+
+        in_queue[pos] = false;
 
         // Skip if already removed or no longer accessible
-        if !grid[row][col] || neighbor_counts[row][col] >= 4 {
+        if !grid[pos] || neighbor_counts[pos] >= 4 {
             continue;
         }
 
         // Remove this cell
-        grid[row][col] = false;
+        grid[pos] = false;
         total_removed += 1;
 
         // Update neighbor counts for adjacent cells and potentially add them to queue
-        for (nr, nc) in get_neighbors(row, col, rows, cols) {
-            if grid[nr][nc] {
-                neighbor_counts[nr][nc] = neighbor_counts[nr][nc].saturating_sub(1);
+        for next in grid.neighbors8(pos) {
+            if grid[next] {
+                neighbor_counts[next] = neighbor_counts[next].saturating_sub(1);
                 // If this cell became accessible and isn't already queued, add it
-                if neighbor_counts[nr][nc] < 4 && !in_queue[nr][nc] {
-                    queue.push_back((nr, nc));
-                    in_queue[nr][nc] = true;
+                if neighbor_counts[next] < 4 && !in_queue[next] {
+                    queue.push_back(next);
+                    in_queue[next] = true;
                 }
             }
         }
     }
 
+    show_paper(&grid, &in_queue, wave, total_removed);
+
     Some(total_removed)
 }
 
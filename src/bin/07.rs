@@ -1,21 +1,33 @@
+use advent_of_code::grid::Grid;
+use advent_of_code::viz::{self, Cell, Color, Frame};
+
 advent_of_code::solution!(7);
 
 /// Parse the grid and find the starting column
-fn parse_grid(input: &str) -> (Vec<Vec<char>>, usize) {
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| l.chars().collect())
-        .collect();
-
-    let start_col = grid[0]
-        .iter()
+fn parse_grid(input: &str) -> (Grid<char>, usize) {
+    let grid = Grid::parse(input, |c| c);
+
+    let (_, start_col) = grid
         .position(|&c| c == 'S')
         .expect("No starting position 'S' found");
 
     (grid, start_col)
 }
 
+/// Show the manifold with the beams traced so far
+fn show_beams(grid: &Grid<char>, trail: &Grid<bool>, splits: u64) {
+    viz::frame(|| {
+        Frame::from_grid(grid, |pos, &c| match c {
+            '^' if pos.0 > 0 && trail[(pos.0 - 1, pos.1)] => Cell::new('^').fg(Color::RED),
+            '^' => Cell::new('^').fg(Color::GRAY),
+            'S' => Cell::new('S').fg(Color::YELLOW),
+            _ if trail[pos] => Cell::new('|').fg(Color::CYAN),
+            _ => Cell::new('.').fg(Color::GRAY),
+        })
+        .caption(format!("{splits} splits"))
+    });
+}
+
 /// Simulate tachyon beams through the manifold and count splits
 /// Uses Vec<bool> instead of HashSet for beam positions
 fn count_splits(input: &str) -> u64 {
@@ -24,14 +36,17 @@ fn count_splits(input: &str) -> u64 {
         return 0;
     }
 
-    let width = grid[0].len();
+    let width = grid.width();
     let mut beams = vec![false; width];
     let mut new_beams = vec![false; width];
     beams[start_col] = true;
 
     let mut split_count: u64 = 0;
 
-    for row in grid.iter().skip(1) {
+    // Beam positions of every row so far, only tracked for the visualisation
+    let mut trail = viz::is_active().then(|| Grid::filled(width, grid.height(), false));
+
+    for (r, row) in grid.rows().enumerate().skip(1) {
         new_beams.fill(false);
         let mut has_beams = false;
 
@@ -58,6 +73,11 @@ fn count_splits(input: &str) -> u64 {
             }
         }
 
+        if let Some(trail) = &mut trail {
+            trail.row_mut(r).copy_from_slice(&new_beams);
+            show_beams(&grid, trail, split_count);
+        }
+
         std::mem::swap(&mut beams, &mut new_beams);
         if !has_beams {
             break;
@@ -75,12 +95,12 @@ fn count_timelines(input: &str) -> u64 {
         return 0;
     }
 
-    let width = grid[0].len();
+    let width = grid.width();
     let mut particles = vec![0u64; width];
     let mut new_particles = vec![0u64; width];
     particles[start_col] = 1;
 
-    for row in grid.iter().skip(1) {
+    for row in grid.rows().skip(1) {
         new_particles.fill(0);
 
         for col in 0..width {
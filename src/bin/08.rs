@@ -1,136 +1,94 @@
-use rayon::prelude::*;
+use advent_of_code::dsu::DisjointSet;
+use advent_of_code::geom::Point3;
+use advent_of_code::parse::signed_ints;
+use advent_of_code::spatial::KdTree;
+use advent_of_code::template::{DayOption, day_args};
+use advent_of_code::viz::{self, Color, Scene, Shape};
+use itertools::Itertools;
 
-advent_of_code::solution!(8);
+advent_of_code::solution!(8, options: DAY_OPTIONS);
 
-/// Union-Find data structure for tracking circuits
-struct UnionFind {
-    parent: Vec<usize>,
-    rank: Vec<usize>,
-    size: Vec<usize>,
-}
-
-impl UnionFind {
-    fn new(n: usize) -> Self {
-        Self {
-            parent: (0..n).collect(),
-            rank: vec![0; n],
-            size: vec![1; n],
-        }
-    }
-
-    fn find(&mut self, x: usize) -> usize {
-        if self.parent[x] != x {
-            self.parent[x] = self.find(self.parent[x]); // Path compression
-        }
-        self.parent[x]
-    }
-
-    fn union(&mut self, x: usize, y: usize) -> bool {
-        let root_x = self.find(x);
-        let root_y = self.find(y);
-
-        if root_x == root_y {
-            return false; // Already in same circuit
-        }
-
-        // Union by rank
-        match self.rank[root_x].cmp(&self.rank[root_y]) {
-            std::cmp::Ordering::Less => {
-                self.parent[root_x] = root_y;
-                self.size[root_y] += self.size[root_x];
-            }
-            std::cmp::Ordering::Greater => {
-                self.parent[root_y] = root_x;
-                self.size[root_x] += self.size[root_y];
-            }
-            std::cmp::Ordering::Equal => {
-                self.parent[root_y] = root_x;
-                self.size[root_x] += self.size[root_y];
-                self.rank[root_x] += 1;
-            }
-        }
-        true
-    }
-
-    fn get_circuit_sizes(&mut self) -> Vec<usize> {
-        let n = self.parent.len();
-        let mut sizes = Vec::new();
-        for i in 0..n {
-            if self.find(i) == i {
-                sizes.push(self.size[i]);
-            }
-        }
-        sizes
-    }
-}
-
-/// Parse a line "X,Y,Z" into coordinates
-fn parse_coord(line: &str) -> Option<(i64, i64, i64)> {
-    let mut parts = line.split(',');
-    let x = parts.next()?.parse().ok()?;
-    let y = parts.next()?.parse().ok()?;
-    let z = parts.next()?.parse().ok()?;
-    Some((x, y, z))
-}
-
-/// Calculate squared Euclidean distance (to avoid sqrt)
-#[inline]
-fn distance_squared(a: (i64, i64, i64), b: (i64, i64, i64)) -> i64 {
-    let dx = a.0 - b.0;
-    let dy = a.1 - b.1;
-    let dz = a.2 - b.2;
-    dx * dx + dy * dy + dz * dz
-}
+const DAY_OPTIONS: &[DayOption] = &[DayOption::value::<usize>("--connections")];
 
 /// Parse coordinates from input
-fn parse_coords(input: &str) -> Vec<(i64, i64, i64)> {
-    input
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .filter_map(parse_coord)
+fn parse_coords(input: &str) -> Vec<Point3> {
+    signed_ints(input)
+        .tuples()
+        .map(|(x, y, z)| Point3::new(x, y, z))
         .collect()
 }
 
-/// Generate all pairs with distances in parallel
-fn generate_pairs_parallel(coords: &[(i64, i64, i64)]) -> Vec<(i64, usize, usize)> {
-    let n = coords.len();
-    (0..n)
-        .into_par_iter()
-        .flat_map_iter(|i| {
-            let coords = coords;
-            ((i + 1)..n).map(move |j| {
-                let dist = distance_squared(coords[i], coords[j]);
-                (dist, i, j)
-            })
-        })
-        .collect()
+/// Isometric projection of a junction box onto the drawing plane
+fn project(p: Point3) -> (f64, f64) {
+    let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+    ((x - z) * 0.866, (x + z) * 0.5 - y)
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let coords = parse_coords(input);
-    let n = coords.len();
-    if n == 0 {
-        return None;
+/// Show the connected junction boxes, with the largest circuits in their own colours
+fn show_circuits(coords: &[Point3], pairs: &[(i64, usize, usize)], uf: &mut DisjointSet) {
+    if !viz::is_active() {
+        return;
     }
 
-    let mut pairs = generate_pairs_parallel(&coords);
-
-    // Use partial sort - we only need the 1000 smallest pairs
-    if pairs.len() > 1000 {
-        pairs.select_nth_unstable_by_key(999, |&(dist, _, _)| dist);
-        pairs.truncate(1000);
+    let mut circuits: Vec<usize> = (0..coords.len())
+        .filter(|&i| uf.find(i) == i && uf.size_of(i) > 1)
+        .collect();
+    circuits.sort_unstable_by_key(|&root| std::cmp::Reverse(uf.size_of(root)));
+    let mut colors = vec![Color::GRAY; coords.len()];
+    for (rank, &root) in circuits.iter().enumerate().take(6) {
+        colors[root] = Color::palette(rank);
     }
+    let color = |uf: &mut DisjointSet, i: usize| colors[uf.find(i)];
+
+    let mut shapes: Vec<Shape> = pairs
+        .iter()
+        .map(|&(_, i, j)| Shape::Line {
+            from: project(coords[i]),
+            to: project(coords[j]),
+            stroke: color(uf, i),
+        })
+        .collect();
+    shapes.extend((0..coords.len()).map(|i| Shape::Dot {
+        at: project(coords[i]),
+        color: color(uf, i),
+    }));
+
+    let caption = format!(
+        "{} connections, {} circuits",
+        pairs.len(),
+        uf.component_count()
+    );
+    viz::scene(|| shapes.into_iter().collect::<Scene>().caption(caption));
+}
+
+/// Connect the `connections` closest pairs of junction boxes, and multiply the sizes of the
+/// three largest circuits
+fn largest_circuits(coords: &[Point3], connections: usize) -> u64 {
+    let tree = KdTree::new(coords.iter().copied());
+    let pairs: Vec<(i64, usize, usize)> = tree.closest_pairs().take(connections).collect();
 
-    let mut uf = UnionFind::new(n);
+    let mut uf = DisjointSet::new(coords.len());
     for &(_, i, j) in &pairs {
         uf.union(i, j);
     }
+    show_circuits(coords, &pairs, &mut uf);
 
     // Get circuit sizes and find 3 largest
-    let mut sizes = uf.get_circuit_sizes();
+    let mut sizes = uf.component_sizes();
     sizes.sort_unstable_by(|a, b| b.cmp(a));
 
-    Some(sizes.iter().take(3).map(|&s| s as u64).product())
+    sizes.iter().take(3).map(|&s| s as u64).product()
+}
+
+/// Day options: `--connections <n>` connects the `n` closest pairs instead of 1000.
+pub fn part_one(input: &str) -> Option<u64> {
+    let coords = parse_coords(input);
+    if coords.is_empty() {
+        return None;
+    }
+
+    let connections = day_args().value("--connections").unwrap_or(1000);
+    Some(largest_circuits(&coords, connections))
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
@@ -140,27 +98,22 @@ pub fn part_two(input: &str) -> Option<u64> {
         return None;
     }
 
-    let mut pairs = generate_pairs_parallel(&coords);
-
-    // Full parallel sort needed for part 2 (process in order)
-    pairs.par_sort_unstable_by_key(|&(dist, _, _)| dist);
-
-    let mut uf = UnionFind::new(n);
-    let mut num_circuits = n;
+    // Only take pairs, closest first, until everything is connected
+    let tree = KdTree::new(coords.iter().copied());
+    let mut uf = DisjointSet::new(n);
     let mut last_connection: Option<(usize, usize)> = None;
 
-    for &(_, i, j) in &pairs {
+    for (_, i, j) in tree.closest_pairs() {
         if uf.union(i, j) {
-            num_circuits -= 1;
             last_connection = Some((i, j));
 
-            if num_circuits == 1 {
+            if uf.component_count() == 1 {
                 break;
             }
         }
     }
 
-    last_connection.map(|(i, j)| coords[i].0 as u64 * coords[j].0 as u64)
+    last_connection.map(|(i, j)| coords[i].x as u64 * coords[j].x as u64)
 }
 
 #[cfg(test)]
@@ -169,21 +122,9 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        // Example uses 10 connections, not 1000 - test the core logic
+        // The example connects 10 pairs, not 1000
         let coords = parse_coords(&advent_of_code::template::read_file("examples", DAY));
-        let n = coords.len();
-        let mut pairs = generate_pairs_parallel(&coords);
-        pairs.select_nth_unstable_by_key(9, |&(dist, _, _)| dist);
-        pairs.truncate(10);
-
-        let mut uf = UnionFind::new(n);
-        for &(_, i, j) in &pairs {
-            uf.union(i, j);
-        }
-        let mut sizes = uf.get_circuit_sizes();
-        sizes.sort_unstable_by(|a, b| b.cmp(a));
-        let result: u64 = sizes.iter().take(3).map(|&s| s as u64).product();
-        assert_eq!(result, 40);
+        assert_eq!(largest_circuits(&coords, 10), 40);
     }
 
     #[test]
@@ -1,26 +1,26 @@
-advent_of_code::solution!(9);
-
+advent_of_code::solution!(9, options: DAY_OPTIONS, report: report);
+
+use advent_of_code::compress::CoordCompressor;
+use advent_of_code::geom::{BoundingBox, OrthogonalPolygon, Point2, polygon_edges};
+use advent_of_code::grid::{Connectivity, Grid};
+use advent_of_code::parse::signed_ints;
+use advent_of_code::template::{DayOption, day_args};
+use advent_of_code::viz::{self, Cell, Color, Frame, Scene, Shape};
 use itertools::Itertools;
-use std::collections::HashMap;
-
-fn parse_coords(input: &str) -> Vec<(i64, i64)> {
-    input
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if line.is_empty() {
-                return None;
-            }
-            let (x, y) = line.split_once(',')?;
-            Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
-        })
+use rayon::prelude::*;
+
+const DAY_OPTIONS: &[DayOption] = &[DayOption::flag("--corners")];
+
+fn parse_coords(input: &str) -> Vec<Point2> {
+    signed_ints(input)
+        .tuples()
+        .map(|(x, y)| Point2::new(x, y))
         .collect()
 }
 
-fn rectangle_area(p1: (i64, i64), p2: (i64, i64)) -> u64 {
-    let width = (p2.0 - p1.0).abs() + 1;
-    let height = (p2.1 - p1.1).abs() + 1;
-    (width * height) as u64
+/// The number of tiles in the rectangle with opposite corners `p1` and `p2`.
+fn rectangle_area(p1: Point2, p2: Point2) -> u64 {
+    BoundingBox::<Point2>::from_corners(p1, p2).point_count()
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
@@ -33,116 +33,148 @@ pub fn part_one(input: &str) -> Option<u64> {
         .max()
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let coords = parse_coords(input);
-    if coords.len() < 3 {
-        return None;
+/// Show the compressed floor: red tiles, the loop of green tiles, its inside, and a rectangle.
+/// Compressing is only done for the visualisation, it's too slow for large inputs otherwise.
+fn show_floor(coords: &[Point2], rectangle: Option<(Point2, Point2)>, caption: &str) {
+    if !viz::is_active() {
+        return;
     }
 
-    // Step 1: Coordinate compression
-    let mut xs: Vec<i64> = coords.iter().map(|p| p.0).collect();
-    let mut ys: Vec<i64> = coords.iter().map(|p| p.1).collect();
-    xs.sort_unstable();
-    xs.dedup();
-    ys.sort_unstable();
-    ys.dedup();
-
-    let x_to_idx: HashMap<i64, usize> = xs.iter().enumerate().map(|(i, &x)| (x, i)).collect();
-    let y_to_idx: HashMap<i64, usize> = ys.iter().enumerate().map(|(i, &y)| (y, i)).collect();
-
-    let nx = xs.len();
-    let ny = ys.len();
-
-    // Step 2: Create expanded grid (2x+1 to handle gaps between coordinates)
-    // Even indices = actual coordinate lines, odd indices = gaps between them
-    let gx = 2 * nx + 1;
-    let gy = 2 * ny + 1;
-
-    // Step 3: Mark boundary edges in expanded grid
-    let mut is_boundary = vec![vec![false; gy]; gx];
-
-    for i in 0..coords.len() {
-        let (x1, y1) = coords[i];
-        let (x2, y2) = coords[(i + 1) % coords.len()];
-
-        // Convert to expanded grid coords (actual coords at even indices, +1 offset)
-        let gx1 = 2 * x_to_idx[&x1] + 1;
-        let gy1 = 2 * y_to_idx[&y1] + 1;
-        let gx2 = 2 * x_to_idx[&x2] + 1;
-        let gy2 = 2 * y_to_idx[&y2] + 1;
-
-        // Draw line in expanded grid
-        if gx1 == gx2 {
-            // Vertical line
-            let (y_min, y_max) = if gy1 < gy2 { (gy1, gy2) } else { (gy2, gy1) };
-            for y in y_min..=y_max {
-                is_boundary[gx1][y] = true;
-            }
-        } else {
-            // Horizontal line
-            let (x_min, x_max) = if gx1 < gx2 { (gx1, gx2) } else { (gx2, gx1) };
-            for x in x_min..=x_max {
-                is_boundary[x][gy1] = true;
-            }
+    // Compress coordinates onto an expanded grid: actual coordinates at odd indices,
+    // the gaps between them (and a margin around the polygon) at even indices.
+    let xs = CoordCompressor::new(coords.iter().map(|p| p.x));
+    let ys = CoordCompressor::new(coords.iter().map(|p| p.y));
+    let cell = |p: Point2| {
+        (
+            ys.expanded_index(p.y).unwrap(),
+            xs.expanded_index(p.x).unwrap(),
+        )
+    };
+
+    // Mark boundary edges in the expanded grid (rows are y, columns are x)
+    let mut is_boundary = Grid::filled(xs.expanded_len(), ys.expanded_len(), false);
+    for (p1, p2) in polygon_edges(coords) {
+        let ((r1, c1), (r2, c2)) = (cell(p1), cell(p2));
+        for r in r1.min(r2)..=r1.max(r2) {
+            is_boundary.row_mut(r)[c1.min(c2)..=c1.max(c2)].fill(true);
         }
     }
 
-    // Step 4: Flood fill from outside (corner 0,0 is always outside)
-    let mut outside = vec![vec![false; gy]; gx];
-    let mut stack = vec![(0usize, 0usize)];
+    // Flood fill from outside (corner 0,0 is always outside)
+    let outside = is_boundary.flood_fill((0, 0), Connectivity::Four, |_, &boundary| !boundary);
+    let rectangle = rectangle.map(|(p1, p2)| (cell(p1), cell(p2)));
+
+    viz::frame(|| {
+        Frame::from_grid(&is_boundary, |(r, c), &boundary| {
+            let in_rectangle = rectangle.is_some_and(|((r1, c1), (r2, c2))| {
+                (r1.min(r2)..=r1.max(r2)).contains(&r) && (c1.min(c2)..=c1.max(c2)).contains(&c)
+            });
+            // Red tiles sit on odd rows and columns, where actual coordinates are
+            let cell = if boundary && r % 2 == 1 && c % 2 == 1 && is_corner(&is_boundary, (r, c)) {
+                Cell::new('#').fg(Color::RED)
+            } else if boundary {
+                Cell::new('X').fg(Color::GREEN)
+            } else if outside[(r, c)] {
+                Cell::new('.').fg(Color::GRAY)
+            } else {
+                Cell::new('x').fg(Color::GREEN)
+            };
+            if in_rectangle {
+                cell.bg(Color::BLUE)
+            } else {
+                cell
+            }
+        })
+        .caption(caption)
+    });
+}
 
-    while let Some((x, y)) = stack.pop() {
-        if x >= gx || y >= gy || is_boundary[x][y] || outside[x][y] {
-            continue;
-        }
-        outside[x][y] = true;
-        if x > 0 {
-            stack.push((x - 1, y));
-        }
-        stack.push((x + 1, y));
-        if y > 0 {
-            stack.push((x, y - 1));
-        }
-        stack.push((x, y + 1));
-    }
+/// Whether the boundary turns at `pos`, i.e. it continues both vertically and horizontally
+fn is_corner(is_boundary: &Grid<bool>, (r, c): (usize, usize)) -> bool {
+    let vertical = is_boundary[(r - 1, c)] || is_boundary[(r + 1, c)];
+    let horizontal = is_boundary[(r, c - 1)] || is_boundary[(r, c + 1)];
+    vertical && horizontal
+}
 
-    // Step 5: Build 2D prefix sum counting OUTSIDE cells
-    let mut prefix = vec![vec![0i64; gy + 1]; gx + 1];
-    for i in 0..gx {
-        for j in 0..gy {
-            prefix[i + 1][j + 1] = prefix[i + 1][j] + prefix[i][j + 1] - prefix[i][j]
-                + if outside[i][j] { 1 } else { 0 };
-        }
-    }
+/// Draw the loop of red and green tiles with the largest rectangle inside it
+fn show_rectangle(coords: &[Point2], p1: Point2, p2: Point2, area: u64) {
+    let point = |p: Point2| (p.x as f64, p.y as f64);
+    viz::scene(|| {
+        let corners = [(p1.x, p1.y), (p2.x, p1.y), (p2.x, p2.y), (p1.x, p2.y)];
+        let shapes = [
+            Shape::Polygon {
+                points: coords.iter().map(|&p| point(p)).collect(),
+                stroke: Color::GREEN,
+                fill: Color::Rgb(30, 70, 40),
+            },
+            Shape::Polygon {
+                points: corners.map(|(x, y)| point(Point2::new(x, y))).to_vec(),
+                stroke: Color::BLUE,
+                fill: Color::Default,
+            },
+        ];
+        let tiles = coords.iter().map(|&p| Shape::Dot {
+            at: point(p),
+            color: Color::RED,
+        });
+        shapes
+            .into_iter()
+            .chain(tiles)
+            .collect::<Scene>()
+            .caption(format!("largest rectangle from {p1} to {p2}: {area} tiles"))
+    });
+}
 
-    // Helper to count outside cells in expanded rectangle [i1, i2] x [j1, j2]
-    let count_outside = |i1: usize, i2: usize, j1: usize, j2: usize| -> i64 {
-        prefix[i2 + 1][j2 + 1] - prefix[i1][j2 + 1] - prefix[i2 + 1][j1] + prefix[i1][j1]
-    };
+/// Find the largest rectangle between two red tiles that only covers red and green tiles,
+/// as its opposite corners.
+fn largest_rectangle(coords: &[Point2]) -> Option<(Point2, Point2)> {
+    if coords.len() < 3 {
+        return None;
+    }
+    let polygon = OrthogonalPolygon::new(coords.to_vec());
+
+    let n = coords.len();
+    let (_, i, j) = (0..n)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let polygon = &polygon;
+            ((i + 1)..n)
+                .filter(move |&j| polygon.contains_vertex_rect(i, j))
+                .map(move |j| (rectangle_area(coords[i], coords[j]), i, j))
+        })
+        .max_by_key(|&(area, i, j)| (area, std::cmp::Reverse((i, j))))?;
 
-    // Step 6: Check all pairs of red tiles
-    let mut max_area = 0u64;
+    Some((coords[i], coords[j]))
+}
 
-    for (idx1, &(x1, y1)) in coords.iter().enumerate() {
-        for &(x2, y2) in coords.iter().skip(idx1 + 1) {
-            // Convert to expanded grid coords
-            let gi1 = 2 * x_to_idx[&x1] + 1;
-            let gj1 = 2 * y_to_idx[&y1] + 1;
-            let gi2 = 2 * x_to_idx[&x2] + 1;
-            let gj2 = 2 * y_to_idx[&y2] + 1;
+/// A description of the rectangle with opposite corners `p1` and `p2`.
+fn describe_rectangle(p1: Point2, p2: Point2) -> String {
+    let area = rectangle_area(p1, p2);
+    format!("largest rectangle from {p1} to {p2}: {area} tiles")
+}
 
-            let (i1, i2) = if gi1 < gi2 { (gi1, gi2) } else { (gi2, gi1) };
-            let (j1, j2) = if gj1 < gj2 { (gj1, gj2) } else { (gj2, gj1) };
+pub fn part_two(input: &str) -> Option<u64> {
+    let coords = parse_coords(input);
+    show_floor(&coords, None, "compressed floor");
 
-            // Rectangle is valid if it contains no outside cells
-            if count_outside(i1, i2, j1, j2) == 0 {
-                let area = rectangle_area((x1, y1), (x2, y2));
-                max_area = max_area.max(area);
-            }
-        }
+    let (p1, p2) = largest_rectangle(&coords)?;
+    let area = rectangle_area(p1, p2);
+    if viz::is_active() {
+        show_floor(&coords, Some((p1, p2)), &describe_rectangle(p1, p2));
     }
+    show_rectangle(&coords, p1, p2, area);
+
+    Some(area)
+}
 
-    Some(max_area)
+/// Day options: `--corners` prints the corners of the largest rectangle after part two.
+fn report(input: &str, part: u8) {
+    if part == 2
+        && day_args().flag("--corners")
+        && let Some((p1, p2)) = largest_rectangle(&parse_coords(input))
+    {
+        eprintln!("{}", describe_rectangle(p1, p2));
+    }
 }
 
 #[cfg(test)]
@@ -160,4 +192,11 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(24));
     }
+
+    #[test]
+    fn test_largest_rectangle() {
+        let coords = parse_coords(&advent_of_code::template::read_file("examples", DAY));
+        let corners = largest_rectangle(&coords);
+        assert_eq!(corners, Some((Point2::new(9, 5), Point2::new(2, 3))));
+    }
 }
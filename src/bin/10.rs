@@ -1,281 +1,289 @@
-advent_of_code::solution!(10);
+advent_of_code::solution!(10, options: DAY_OPTIONS, report: report);
+
+use advent_of_code::linalg::{minimize_ilp, solve_gf2};
+use advent_of_code::parse::{Cursor, Digits, ParseResult};
+use advent_of_code::template::{DayOption, day_args};
+use itertools::Itertools;
+
+const DAY_OPTIONS: &[DayOption] = &[DayOption::flag("--explain")];
+
+/// A machine: the indicator light pattern as a bitmask, each button as the indices it affects,
+/// and the joltage targets
+struct Machine {
+    lights: u64,
+    light_count: usize,
+    buttons: Vec<Vec<usize>>,
+    joltages: Vec<u64>,
+}
 
-/// Parse a machine line into (target_bitmask, buttons_bitmasks) for Part 1
-fn parse_machine(line: &str) -> Option<(u64, Vec<u64>)> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
+impl Machine {
+    /// The buttons as bitmasks of the lights they toggle
+    fn button_masks(&self) -> Vec<u64> {
+        self.buttons
+            .iter()
+            .map(|button| button.iter().fold(0, |acc, &idx| acc | 1 << idx))
+            .collect()
     }
 
-    // Find the pattern between []
-    let bracket_start = line.find('[')?;
-    let bracket_end = line.find(']')?;
-    let pattern = &line[bracket_start + 1..bracket_end];
-
-    // Convert pattern to target bitmask (# = on = 1, . = off = 0)
-    let mut target: u64 = 0;
-    for (i, c) in pattern.chars().enumerate() {
-        if c == '#' {
-            target |= 1 << i;
-        }
+    /// The lights that are on after pressing each button `presses[i]` times
+    fn lights_after(&self, presses: &[u64]) -> u64 {
+        self.button_masks()
+            .iter()
+            .zip(presses)
+            .filter(|&(_, &n)| n % 2 == 1)
+            .fold(0, |acc, (&mask, _)| acc ^ mask)
     }
 
-    // Find where joltages start (curly brace)
-    let joltage_start = line.find('{')?;
-
-    // Parse all buttons between ] and {
-    let button_section = &line[bracket_end + 1..joltage_start];
-    let mut buttons = Vec::new();
-
-    let mut i = 0;
-    let chars: Vec<char> = button_section.chars().collect();
-    while i < chars.len() {
-        if chars[i] == '(' {
-            // Find matching )
-            let start = i + 1;
-            while i < chars.len() && chars[i] != ')' {
-                i += 1;
-            }
-            let button_str: String = chars[start..i].iter().collect();
-
-            // Parse the indices
-            let mut button_mask: u64 = 0;
-            for num_str in button_str.split(',') {
-                if let Ok(idx) = num_str.trim().parse::<usize>() {
-                    button_mask |= 1 << idx;
-                }
+    /// The joltage levels after pressing each button `presses[i]` times
+    fn joltages_after(&self, presses: &[u64]) -> Vec<u64> {
+        let n_counters = self.joltages.len();
+        let mut joltages = vec![0; n_counters];
+        for (button, &n) in self.buttons.iter().zip(presses) {
+            for &idx in button.iter().filter(|&&idx| idx < n_counters) {
+                joltages[idx] += n;
             }
-            buttons.push(button_mask);
         }
-        i += 1;
+        joltages
     }
-
-    Some((target, buttons))
 }
 
-/// Parse a machine line for Part 2: (joltage_targets, buttons as index lists)
-fn parse_machine_part2(line: &str) -> Option<(Vec<u64>, Vec<Vec<usize>>)> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
-    }
-
-    // Find where joltages start and end
-    let joltage_start = line.find('{')?;
-    let joltage_end = line.find('}')?;
-    let joltage_str = &line[joltage_start + 1..joltage_end];
-
-    // Parse joltage targets
-    let targets: Vec<u64> = joltage_str
-        .split(',')
-        .filter_map(|s| s.trim().parse().ok())
-        .collect();
-
-    // Find where buttons section ends (at joltage start)
-    let bracket_end = line.find(']')?;
-    let button_section = &line[bracket_end + 1..joltage_start];
-
-    // Parse all buttons
-    let mut buttons: Vec<Vec<usize>> = Vec::new();
-    let mut i = 0;
-    let chars: Vec<char> = button_section.chars().collect();
-    while i < chars.len() {
-        if chars[i] == '(' {
-            let start = i + 1;
-            while i < chars.len() && chars[i] != ')' {
-                i += 1;
-            }
-            let button_str: String = chars[start..i].iter().collect();
-
-            let indices: Vec<usize> = button_str
-                .split(',')
-                .filter_map(|s| s.trim().parse().ok())
-                .collect();
-            buttons.push(indices);
-        }
-        i += 1;
-    }
-
-    Some((targets, buttons))
+/// Parse a comma-separated list of numbers like "1,3"
+fn numbers<T: Digits>(cursor: &mut Cursor) -> ParseResult<Vec<T>> {
+    cursor.separated(",", Cursor::unsigned)
 }
 
-/// Find minimum button presses to reach target state (Part 1: toggle/XOR)
-fn min_presses_part1(target: u64, buttons: &[u64]) -> u64 {
-    let n = buttons.len();
-    let mut min_presses = u64::MAX;
-
-    // Try all 2^n combinations of buttons (each pressed 0 or 1 times)
-    for mask in 0..(1u64 << n) {
-        let mut result: u64 = 0;
-        let mut presses = 0;
-
-        for (i, &button) in buttons.iter().enumerate() {
-            if mask & (1 << i) != 0 {
-                result ^= button;
-                presses += 1;
-            }
-        }
+/// Parse a machine line like "[.##.] (3) (1,3) (2) {3,5,4}"
+fn parse_machine(line: &str) -> ParseResult<Machine> {
+    let mut cursor = Cursor::new(line.trim());
 
-        if result == target && presses < min_presses {
-            min_presses = presses;
-        }
-    }
-
-    min_presses
+    // Convert pattern to target bitmask (# = on = 1, . = off = 0)
+    let pattern = cursor.delimited("[", "]", |c| Ok(c.take_while(|ch| "#.".contains(ch))))?;
+    let lights = pattern
+        .bytes()
+        .enumerate()
+        .filter(|&(_, b)| b == b'#')
+        .fold(0, |acc, (i, _)| acc | 1 << i);
+
+    let buttons = cursor.many(|c| c.delimited("(", ")", numbers));
+
+    cursor.skip_whitespace();
+    let joltages = cursor.delimited("{", "}", numbers)?;
+    cursor.finish()?;
+
+    Ok(Machine {
+        lights,
+        light_count: pattern.len(),
+        buttons,
+        joltages,
+    })
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let mut total = 0;
+/// Parse all machines, skipping blank or malformed lines
+fn parse_machines(input: &str) -> impl Iterator<Item = Machine> + '_ {
+    input.lines().filter_map(|line| parse_machine(line).ok())
+}
 
-    for line in input.lines() {
-        if let Some((target, buttons)) = parse_machine(line) {
-            let presses = min_presses_part1(target, &buttons);
-            if presses != u64::MAX {
-                total += presses;
-            }
-        }
-    }
+/// Find which buttons to press to reach the target state with the fewest presses, as a bitmask
+/// (Part 1: toggle/XOR). Pressing a button twice undoes it, so each is pressed at most once.
+/// Each light gives one equation over GF(2): the buttons toggling it must XOR to its target bit.
+fn min_presses_part1(target: u64, buttons: &[u64]) -> Option<u64> {
+    let lights = 64
+        - buttons
+            .iter()
+            .fold(target, |acc, &b| acc | b)
+            .leading_zeros();
+
+    let equations: Vec<(u64, bool)> = (0..lights)
+        .map(|light| {
+            let mask = buttons
+                .iter()
+                .enumerate()
+                .filter(|&(_, &button)| button & (1 << light) != 0)
+                .fold(0, |acc, (i, _)| acc | (1 << i));
+            (mask, target & (1 << light) != 0)
+        })
+        .collect();
 
-    Some(total)
+    Some(solve_gf2(&equations, buttons.len())?.min_weight())
 }
 
-/// Find minimum button presses to reach target joltage levels (Part 2: addition)
-/// Uses the "bifurcate" algorithm: enumerate parity-matching combos, halve, and recurse
-fn min_presses_part2(targets: &[u64], buttons: &[Vec<usize>]) -> u64 {
-    use std::collections::HashMap;
-
+/// Find how often to press each button to reach the target joltage levels with the fewest
+/// presses (Part 2: addition). Solves `A * presses = targets` as an integer linear program
+/// minimising the total presses.
+fn min_presses_part2(targets: &[u64], buttons: &[Vec<usize>]) -> Option<Vec<u64>> {
     let n_counters = targets.len();
 
-    if targets.iter().all(|&t| t == 0) {
-        return 0;
+    // a[counter][button] = 1 if the button increments the counter
+    let mut a = vec![vec![0i64; buttons.len()]; n_counters];
+    for (j, button) in buttons.iter().enumerate() {
+        for &idx in button.iter().filter(|&&idx| idx < n_counters) {
+            a[idx][j] = 1;
+        }
     }
 
-    // Build button masks for XOR parity calculation (like Part 1)
-    let button_masks: Vec<u64> = buttons
+    // A button can be pressed at most as often as the smallest target among its counters
+    let upper: Vec<i64> = buttons
         .iter()
         .map(|button| {
-            let mut mask = 0u64;
-            for &idx in button {
-                if idx < n_counters {
-                    mask |= 1 << idx;
-                }
-            }
-            mask
+            button
+                .iter()
+                .filter_map(|&idx| targets.get(idx))
+                .min()
+                .map_or(0, |&t| t as i64)
         })
         .collect();
 
-    // Build button effects lists
-    let effects: Vec<Vec<usize>> = buttons
-        .iter()
-        .map(|b| b.iter().filter(|&&i| i < n_counters).copied().collect())
-        .collect();
+    let b: Vec<i64> = targets.iter().map(|&t| t as i64).collect();
+    let cost = vec![1; buttons.len()];
 
-    // Calculate parity mask (which bits are odd)
-    fn parity_mask(targets: &[u64]) -> u64 {
-        let mut mask = 0u64;
-        for (i, &t) in targets.iter().enumerate() {
-            if t % 2 == 1 {
-                mask |= 1 << i;
-            }
-        }
-        mask
-    }
+    let (_, presses) = minimize_ilp(&cost, &a, &b, &upper)?;
+    Some(presses.into_iter().map(|n| n as u64).collect())
+}
 
-    fn solve(
-        targets: Vec<u64>,
-        button_masks: &[u64],
-        effects: &[Vec<usize>],
-        cache: &mut HashMap<Vec<u64>, u64>,
-    ) -> u64 {
-        // Base case: all zeros
-        if targets.iter().all(|&t| t == 0) {
-            return 0;
-        }
+/// What the buttons of a machine are pressed for: the indicator lights or the joltage levels
+#[derive(Clone, Copy)]
+enum Target {
+    Lights,
+    Joltages,
+}
 
-        // Check cache
-        if let Some(&result) = cache.get(&targets) {
-            return result;
+impl Target {
+    /// The fewest presses to reach the target on `machine`, if it can be reached at all
+    fn fewest_presses(self, machine: &Machine) -> Option<u64> {
+        match self {
+            Target::Lights => min_presses_part1(machine.lights, &machine.button_masks())
+                .map(|pressed| u64::from(pressed.count_ones())),
+            Target::Joltages => min_presses_part2(&machine.joltages, &machine.buttons)
+                .map(|presses| presses.iter().sum()),
         }
+    }
 
-        let n_buttons = button_masks.len();
-        let target_parity = parity_mask(&targets);
-
-        let mut min_presses = u64::MAX;
-
-        // Enumerate all 2^B button combinations for Phase 1
-        for combo in 0..(1u64 << n_buttons) {
-            // Calculate parity this combo achieves (XOR of selected button masks)
-            let mut achieved_parity = 0u64;
-            let mut phase1_count = 0u64;
-
-            for (i, &mask) in button_masks.iter().enumerate() {
-                if combo & (1 << i) != 0 {
-                    achieved_parity ^= mask;
-                    phase1_count += 1;
-                }
+    /// How often to press each button of `machine` to reach the target with the fewest presses,
+    /// if it can be reached at all
+    fn min_presses(self, machine: &Machine) -> Option<Vec<u64>> {
+        match self {
+            Target::Lights => {
+                let pressed = min_presses_part1(machine.lights, &machine.button_masks())?;
+                let presses = (0..machine.buttons.len()).map(|i| pressed >> i & 1);
+                Some(presses.collect())
             }
+            Target::Joltages => min_presses_part2(&machine.joltages, &machine.buttons),
+        }
+    }
 
-            // Must match target parity for remaining to be all even
-            if achieved_parity != target_parity {
-                continue;
+    /// What pressing the buttons `presses[i]` times leads to, formatted like in the input, and
+    /// whether that is the target
+    fn reached(self, machine: &Machine, presses: &[u64]) -> (String, bool) {
+        match self {
+            Target::Lights => {
+                let lights = machine.lights_after(presses);
+                let pattern: String = (0..machine.light_count)
+                    .map(|i| if lights >> i & 1 == 1 { '#' } else { '.' })
+                    .collect();
+                (format!("[{pattern}]"), lights == machine.lights)
             }
-
-            // Calculate remaining targets after pressing Phase 1 buttons once each
-            let mut remaining = targets.clone();
-            let mut valid = true;
-
-            for (btn_idx, btn_effects) in effects.iter().enumerate() {
-                if combo & (1 << btn_idx) != 0 {
-                    for &counter_idx in btn_effects {
-                        if remaining[counter_idx] == 0 {
-                            valid = false;
-                            break;
-                        }
-                        remaining[counter_idx] -= 1;
-                    }
-                    if !valid {
-                        break;
-                    }
-                }
+            Target::Joltages => {
+                let joltages = machine.joltages_after(presses);
+                (
+                    format!("{{{}}}", joltages.iter().join(",")),
+                    joltages == machine.joltages,
+                )
             }
+        }
+    }
+}
 
-            if !valid {
-                continue;
-            }
+/// Print a table of the presses chosen for every machine, what they lead to, and whether that
+/// is the target
+fn explain(machines: &[Machine], presses: &[Option<Vec<u64>>], target: Target) {
+    let rows: Vec<[String; 5]> = machines
+        .iter()
+        .zip(presses)
+        .enumerate()
+        .map(|(i, (machine, presses))| {
+            let Some(presses) = presses else {
+                return [
+                    (i + 1).to_string(),
+                    "-".into(),
+                    "no solution".into(),
+                    "".into(),
+                    "-".into(),
+                ];
+            };
+            // The pressed buttons with how often, like `(0,2)x3 (1)x1`
+            let pressed = machine
+                .buttons
+                .iter()
+                .zip(presses)
+                .filter(|&(_, &n)| n > 0)
+                .map(|(button, n)| format!("({})x{n}", button.iter().join(",")))
+                .join(" ");
+            let (reached, verified) = target.reached(machine, presses);
+            [
+                (i + 1).to_string(),
+                presses.iter().sum::<u64>().to_string(),
+                pressed,
+                reached,
+                if verified { "ok" } else { "WRONG" }.to_string(),
+            ]
+        })
+        .collect();
 
-            // All remaining should now be even - halve them
-            let halved: Vec<u64> = remaining.iter().map(|&r| r / 2).collect();
+    let header = ["machine", "presses", "buttons", "reached", "check"].map(String::from);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[col].len())
+                .max()
+                .unwrap()
+        })
+        .collect();
+    for row in [&header].into_iter().chain(&rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .join(" | ");
+        eprintln!("{}", line.trim_end());
+    }
+}
 
-            // Recurse on halved problem
-            let sub_result = solve(halved, button_masks, effects, cache);
-            if sub_result != u64::MAX {
-                // Total = Phase 1 presses + 2 * (recursive result)
-                let total = phase1_count + 2 * sub_result;
-                min_presses = min_presses.min(total);
-            }
-        }
+/// Sum the fewest presses over all machines, skipping machines without a solution
+fn total_presses(input: &str, target: Target) -> u64 {
+    parse_machines(input)
+        .filter_map(|machine| target.fewest_presses(&machine))
+        .sum()
+}
 
-        cache.insert(targets, min_presses);
-        min_presses
-    }
+/// How often to press each button of every machine, `None` for machines without a solution
+fn all_presses(machines: &[Machine], target: Target) -> Vec<Option<Vec<u64>>> {
+    machines
+        .iter()
+        .map(|machine| target.min_presses(machine))
+        .collect()
+}
 
-    let mut cache = HashMap::new();
-    solve(targets.to_vec(), &button_masks, &effects, &mut cache)
+pub fn part_one(input: &str) -> Option<u64> {
+    Some(total_presses(input, Target::Lights))
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let mut total = 0;
+    Some(total_presses(input, Target::Joltages))
+}
 
-    for line in input.lines() {
-        if let Some((targets, buttons)) = parse_machine_part2(line) {
-            let presses = min_presses_part2(&targets, &buttons);
-            if presses != u64::MAX {
-                total += presses;
-            }
-        }
+/// Day options: `--explain` prints the chosen presses of every machine after each part, and
+/// checks them.
+fn report(input: &str, part: u8) {
+    if !day_args().flag("--explain") {
+        return;
     }
-
-    Some(total)
+    let target = match part {
+        1 => Target::Lights,
+        _ => Target::Joltages,
+    };
+    let machines: Vec<Machine> = parse_machines(input).collect();
+    explain(&machines, &all_presses(&machines, target), target);
 }
 
 #[cfg(test)]
@@ -293,4 +301,34 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(33)); // 10 + 12 + 11 = 33
     }
+
+    #[test]
+    fn test_explanations() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let machines: Vec<Machine> = parse_machines(&input).collect();
+
+        for (target, totals) in [
+            (Target::Lights, [2, 3, 2]),
+            (Target::Joltages, [10, 12, 11]),
+        ] {
+            let all = all_presses(&machines, target);
+            for ((machine, presses), total) in machines.iter().zip(all).zip(totals) {
+                let presses = presses.unwrap();
+                assert_eq!(presses.iter().sum::<u64>(), total);
+                assert_eq!(target.fewest_presses(machine), Some(total));
+                assert!(target.reached(machine, &presses).1);
+            }
+        }
+
+        let presses = Target::Lights.min_presses(&machines[0]).unwrap();
+        assert_eq!(presses, vec![0, 1, 0, 1, 0, 0]);
+        assert_eq!(
+            Target::Lights.reached(&machines[0], &presses),
+            ("[.##.]".to_string(), true)
+        );
+        assert_eq!(
+            Target::Joltages.reached(&machines[0], &[1, 0, 0, 0, 0, 0]),
+            ("{0,0,0,1}".to_string(), false)
+        );
+    }
 }
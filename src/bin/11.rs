@@ -1,10 +1,33 @@
-advent_of_code::solution!(11);
+advent_of_code::solution!(11, options: DAY_OPTIONS, setup: load_queries, report: report);
 
-use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use advent_of_code::graph::{CycleError, Graph, MAX_REQUIRED, PathCounts};
+use advent_of_code::template::solution_args::DayArgs;
+use advent_of_code::template::{DayOption, day_args};
+use itertools::Itertools;
+use num_bigint::BigUint;
+
+const DAY_OPTIONS: &[DayOption] = &[
+    DayOption::value::<String>("--from"),
+    DayOption::value::<String>("--to"),
+    DayOption::value::<String>("--via"),
+    DayOption::value::<String>("--avoid"),
+    DayOption::value::<String>("--query"),
+    DayOption::value::<usize>("--paths"),
+];
+
+/// The day options a query file may hold.
+const QUERY_OPTIONS: &[DayOption] = &[
+    DayOption::value::<String>("--from"),
+    DayOption::value::<String>("--to"),
+    DayOption::value::<String>("--via"),
+    DayOption::value::<String>("--avoid"),
+];
 
 /// Parse the input into a graph (device -> list of outputs)
-fn parse_graph(input: &str) -> HashMap<&str, Vec<&str>> {
-    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+fn parse_graph(input: &str) -> Graph<&str> {
+    let mut graph = Graph::new();
 
     for line in input.lines() {
         let line = line.trim();
@@ -14,105 +37,145 @@ fn parse_graph(input: &str) -> HashMap<&str, Vec<&str>> {
 
         let mut parts = line.split(':');
         let device = parts.next().unwrap().trim();
-        let outputs: Vec<&str> = parts
-            .next()
-            .unwrap_or("")
-            .split_whitespace()
-            .collect();
+        graph.add_node(device);
 
-        graph.insert(device, outputs);
+        for output in parts.next().unwrap_or("").split_whitespace() {
+            graph.add_edge(device, output);
+        }
     }
 
     graph
 }
 
-/// Count all paths from start to "out" using DFS with memoization
-fn count_paths<'a>(
-    graph: &HashMap<&'a str, Vec<&'a str>>,
-    current: &'a str,
-    memo: &mut HashMap<&'a str, u64>,
-) -> u64 {
-    // Base case: reached the destination
-    if current == "out" {
-        return 1;
+/// Paths from `from` to `to` that visit every device in `via` and none in `avoid`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Query {
+    from: String,
+    to: String,
+    via: Vec<String>,
+    avoid: Vec<String>,
+}
+
+impl Query {
+    fn new(from: &str, to: &str, via: &[&str]) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            via: via.iter().map(|name| name.to_string()).collect(),
+            avoid: vec![],
+        }
     }
 
-    // Check memoization
-    if let Some(&cached) = memo.get(current) {
-        return cached;
+    /// Replaces the parts of the query given as day options. A query file passed with `--query`
+    /// holds the same options, e.g. `--from svr --via dac --via fft`, and `#` starts a comment.
+    /// Options on the command line take precedence over the file.
+    fn with_options(self, args: &DayArgs) -> Result<Self, String> {
+        let query = match args.value::<String>("--query") {
+            Some(path) => {
+                let file = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("could not read query file {path}: {e}"))?;
+                let options = file
+                    .lines()
+                    .flat_map(|line| line.split('#').next().unwrap().split_whitespace());
+                let options = DayArgs::new(options);
+                options
+                    .validate(QUERY_OPTIONS)
+                    .map_err(|e| format!("invalid query file {path}: {e}"))?;
+                self.with_options(&options)?
+            }
+            None => self,
+        };
+
+        let (via, avoid) = (args.values("--via"), args.values("--avoid"));
+        let via = if via.is_empty() { query.via } else { via };
+        if via.len() > MAX_REQUIRED {
+            return Err(format!(
+                "at most {MAX_REQUIRED} devices can be passed with `--via`, got {}",
+                via.len()
+            ));
+        }
+        Ok(Self {
+            from: args.value("--from").unwrap_or(query.from),
+            to: args.value("--to").unwrap_or(query.to),
+            via,
+            avoid: if avoid.is_empty() { query.avoid } else { avoid },
+        })
     }
+}
 
-    // Get outputs for current device
-    let outputs = match graph.get(current) {
-        Some(outputs) => outputs,
-        None => return 0, // Dead end
+/// The paths of `query`, or `Ok(None)` if one of the devices it goes from, to or via does not
+/// appear in the input, so that there are no paths.
+fn query_paths<'a>(
+    graph: &'a Graph<&'a str>,
+    query: &Query,
+) -> Result<Option<PathCounts<'a, &'a str, BigUint>>, CycleError> {
+    let id = |name: &String| graph.id(&name.as_str());
+    let (Some(from), Some(to)) = (id(&query.from), id(&query.to)) else {
+        return Ok(None);
     };
+    let Some(via) = query.via.iter().map(id).collect::<Option<Vec<_>>>() else {
+        return Ok(None);
+    };
+    let avoid: Vec<_> = query.avoid.iter().filter_map(id).collect();
 
-    // Sum paths through all outputs
-    let total: u64 = outputs
-        .iter()
-        .map(|&next| count_paths(graph, next, memo))
-        .sum();
-
-    memo.insert(current, total);
-    total
+    graph.path_counts(from, to, &via, &avoid).map(Some)
 }
 
-/// Count paths from current to "out" that visit both dac and fft
-/// State: (node, visited_dac, visited_fft) where visited flags are encoded as bits
-fn count_paths_through_both<'a>(
-    graph: &HashMap<&'a str, Vec<&'a str>>,
-    current: &'a str,
-    visited_dac: bool,
-    visited_fft: bool,
-    memo: &mut HashMap<(&'a str, bool, bool), u64>,
-) -> u64 {
-    // Update visited flags based on current node
-    let visited_dac = visited_dac || current == "dac";
-    let visited_fft = visited_fft || current == "fft";
-
-    // Base case: reached the destination
-    if current == "out" {
-        // Only count if we visited both dac and fft
-        return if visited_dac && visited_fft { 1 } else { 0 };
-    }
-
-    // Check memoization
-    let key = (current, visited_dac, visited_fft);
-    if let Some(&cached) = memo.get(&key) {
-        return cached;
+/// Count the paths of `query`, `None` if the graph has a cycle.
+fn count_paths(graph: &Graph<&str>, query: &Query) -> Option<BigUint> {
+    match query_paths(graph, query).ok()? {
+        Some(counts) => Some(counts.count().clone()),
+        None => Some(BigUint::ZERO),
     }
+}
 
-    // Get outputs for current device
-    let outputs = match graph.get(current) {
-        Some(outputs) => outputs,
-        None => return 0, // Dead end
+/// Up to `limit` of the paths of `query`, formatted like `svr -> aaa -> out`.
+fn sample_paths(graph: &Graph<&str>, query: &Query, limit: usize) -> Vec<String> {
+    let Ok(Some(counts)) = query_paths(graph, query) else {
+        return vec![];
     };
+    let paths = counts.paths(limit);
+    let names = |path: &Vec<_>| path.iter().map(|&node| graph.name(node)).join(" -> ");
+    paths.iter().map(names).collect()
+}
 
-    // Sum paths through all outputs
-    let total: u64 = outputs
-        .iter()
-        .map(|&next| count_paths_through_both(graph, next, visited_dac, visited_fft, memo))
-        .sum();
+/// The queries of both parts, adjusted by the day options. Loaded by `load_queries` before any
+/// part runs, so that the parts don't read query files.
+static QUERIES: LazyLock<Result<[Query; 2], String>> = LazyLock::new(|| {
+    let args = day_args();
+    Ok([
+        Query::new("you", "out", &[]).with_options(args)?,
+        Query::new("svr", "out", &["dac", "fft"]).with_options(args)?,
+    ])
+});
 
-    memo.insert(key, total);
-    total
+/// Day options: `--from`, `--to`, `--via` and `--avoid` (both repeatable) change the queries,
+/// and `--query <file>` reads them from a file.
+fn load_queries() -> Result<(), String> {
+    QUERIES.as_ref().map(drop).map_err(Clone::clone)
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let graph = parse_graph(input);
-    let mut memo: HashMap<&str, u64> = HashMap::new();
+/// The query of `part`.
+fn query(part: u8) -> &'static Query {
+    let queries = QUERIES.as_ref().expect("`load_queries` checks the queries");
+    &queries[usize::from(part) - 1]
+}
 
-    let count = count_paths(&graph, "you", &mut memo);
-    Some(count)
+pub fn part_one(input: &str) -> Option<BigUint> {
+    count_paths(&parse_graph(input), query(1))
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let graph = parse_graph(input);
-    let mut memo: HashMap<(&str, bool, bool), u64> = HashMap::new();
+pub fn part_two(input: &str) -> Option<BigUint> {
+    count_paths(&parse_graph(input), query(2))
+}
 
-    let count = count_paths_through_both(&graph, "svr", false, false, &mut memo);
-    Some(count)
+/// Day options: `--paths <n>` prints up to `n` of the paths after each part.
+fn report(input: &str, part: u8) {
+    if let Some(limit) = day_args().value("--paths") {
+        for path in sample_paths(&parse_graph(input), query(part), limit) {
+            eprintln!("{path}");
+        }
+    }
 }
 
 #[cfg(test)]
@@ -122,12 +185,88 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(5));
+        assert_eq!(result, Some(5u8.into()));
     }
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file_part("examples", DAY, 2));
-        assert_eq!(result, Some(2));
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(result, Some(2u8.into()));
+    }
+
+    #[test]
+    fn test_queries() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 2);
+        let graph = parse_graph(&input);
+        let args = DayArgs::new(["--via", "fft", "--avoid", "hub", "--avoid", "eee"]);
+        let query = Query::new("svr", "out", &["dac", "fft"])
+            .with_options(&args)
+            .unwrap();
+        assert_eq!(
+            query,
+            Query {
+                from: "svr".to_string(),
+                to: "out".to_string(),
+                via: vec!["fft".to_string()],
+                avoid: vec!["hub".to_string(), "eee".to_string()],
+            }
+        );
+        assert_eq!(count_paths(&graph, &query), Some(0u8.into()));
+
+        let query = Query::new("svr", "fff", &["dac"]);
+        assert_eq!(count_paths(&graph, &query), Some(2u8.into()));
+        assert_eq!(
+            sample_paths(&graph, &query, 1),
+            vec!["svr -> aaa -> fft -> ccc -> eee -> dac -> fff"]
+        );
+        let via = ["--via", "dac"].repeat(MAX_REQUIRED + 1);
+        let too_many = Query::new("svr", "out", &[]).with_options(&DayArgs::new(via));
+        assert!(too_many.unwrap_err().contains("at most 16 devices"));
+
+        let query = Query::new("svr", "out", &["nowhere"]);
+        assert_eq!(count_paths(&graph, &query), Some(0u8.into()));
+        assert!(sample_paths(&graph, &query, 1).is_empty());
+    }
+
+    #[test]
+    fn test_query_files() {
+        let path = std::env::temp_dir().join(format!("aoc-day11-{}.txt", std::process::id()));
+        let query_file = |contents: &str, args: &[&str]| {
+            std::fs::write(&path, contents).unwrap();
+            let args = DayArgs::new(
+                ["--query", path.to_str().unwrap()]
+                    .iter()
+                    .chain(args)
+                    .copied(),
+            );
+            Query::new("svr", "out", &["dac", "fft"]).with_options(&args)
+        };
+
+        let query = query_file("# paths into fff\n--to fff --via dac\n", &["--from", "ccc"]);
+        let nested = query_file("--query query.txt\n", &[]);
+        let malformed = query_file("--via\n", &[]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(query, Ok(Query::new("ccc", "fff", &["dac"])));
+        assert!(nested.unwrap_err().contains("unknown day option `--query`"));
+        assert!(malformed.unwrap_err().contains("needs a value"));
+
+        // The file is gone now
+        let args = DayArgs::new(["--query", path.to_str().unwrap()]);
+        let missing = Query::new("svr", "out", &[]).with_options(&args);
+        assert!(missing.unwrap_err().contains("could not read"));
+    }
+
+    #[test]
+    fn test_big_counts() {
+        // A chain of 80 diamonds has 2^80 paths
+        let input = (0..80)
+            .map(|i| format!("n{i}: a{i} b{i}\na{i}: n{}\nb{i}: n{}", i + 1, i + 1))
+            .join("\n");
+        let graph = parse_graph(&input);
+        let query = Query::new("n0", "n80", &["a3"]);
+        assert_eq!(count_paths(&graph, &query), Some(BigUint::from(1u8) << 79));
     }
 }
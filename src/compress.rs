@@ -0,0 +1,102 @@
+//! Coordinate compression: maps a sparse set of coordinates onto dense indices.
+//!
+//! The expanded indexing places each coordinate on an odd index and leaves an even index
+//! before, between and after them, so that gaps between coordinates keep a cell of their own.
+
+/// The sorted, distinct values of a set of coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoordCompressor<T> {
+    values: Vec<T>,
+}
+
+impl<T: Ord + Copy> CoordCompressor<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        Self { values }
+    }
+
+    /// The number of distinct values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The distinct values in ascending order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The dense index of `value`, if it is one of the compressed values.
+    pub fn index(&self, value: T) -> Option<usize> {
+        self.values.binary_search(&value).ok()
+    }
+
+    /// The value at dense index `index`.
+    pub fn value(&self, index: usize) -> T {
+        self.values[index]
+    }
+
+    /// The number of compressed values less than `value`.
+    pub fn lower_bound(&self, value: T) -> usize {
+        self.values.partition_point(|&x| x < value)
+    }
+
+    /// The number of compressed values less than or equal to `value`.
+    pub fn upper_bound(&self, value: T) -> usize {
+        self.values.partition_point(|&x| x <= value)
+    }
+
+    /// The number of cells in the expanded indexing, `2 * len() + 1`.
+    pub fn expanded_len(&self) -> usize {
+        2 * self.len() + 1
+    }
+
+    /// The expanded index of `value`, if it is one of the compressed values. Always odd.
+    pub fn expanded_index(&self, value: T) -> Option<usize> {
+        self.index(value).map(|i| 2 * i + 1)
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<T> for CoordCompressor<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CoordCompressor;
+
+    #[test]
+    fn compresses_values() {
+        let xs: CoordCompressor<i64> = [30, -5, 10, 30, 7].into_iter().collect();
+        assert_eq!(xs.values(), &[-5, 7, 10, 30]);
+        assert_eq!(xs.len(), 4);
+        assert_eq!(xs.index(10), Some(2));
+        assert_eq!(xs.index(11), None);
+        assert_eq!(xs.value(3), 30);
+    }
+
+    #[test]
+    fn answers_rank_queries() {
+        let xs = CoordCompressor::new([2, 4, 8]);
+        assert_eq!(xs.lower_bound(4), 1);
+        assert_eq!(xs.upper_bound(4), 2);
+        assert_eq!(xs.lower_bound(0), 0);
+        assert_eq!(xs.upper_bound(9), 3);
+    }
+
+    #[test]
+    fn expands_with_gaps() {
+        let xs = CoordCompressor::new([100_u32, 5]);
+        assert_eq!(xs.expanded_len(), 5);
+        assert_eq!(xs.expanded_index(5), Some(1));
+        assert_eq!(xs.expanded_index(100), Some(3));
+        assert_eq!(xs.expanded_index(6), None);
+    }
+}
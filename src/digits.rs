@@ -0,0 +1,117 @@
+//! Decimal digit sequences of any length: parsing, picking the largest subsequence of a given
+//! length, and conversion to a decimal string or a big integer.
+//!
+//! Digits are kept as their values `0..=9`, most significant first, so that numbers too long for
+//! any primitive integer can still be compared and built digit by digit.
+
+use num_bigint::BigUint;
+
+/// The digits of `text`, or `None` if it contains anything other than ASCII digits.
+pub fn parse_digits(text: &str) -> Option<Vec<u8>> {
+    text.bytes()
+        .map(|b| b.is_ascii_digit().then(|| b - b'0'))
+        .collect()
+}
+
+/// The lexicographically largest subsequence of `items` with exactly `k` elements, or `None` if
+/// there are fewer than `k`. For digits, that is the largest `k`-digit number obtainable by
+/// deleting digits.
+///
+/// Keeps a monotonic stack of the chosen items in `O(n)`: a new item replaces the smaller items
+/// at the top, as long as enough items are left to still fill all `k` places.
+pub fn largest_subsequence<T: Ord + Copy>(items: &[T], k: usize) -> Option<Vec<T>> {
+    if k > items.len() {
+        return None;
+    }
+
+    let mut stack = Vec::with_capacity(k);
+    for (i, &item) in items.iter().enumerate() {
+        let left = items.len() - i;
+        while stack.last().is_some_and(|&top| top < item) && stack.len() - 1 + left >= k {
+            stack.pop();
+        }
+        if stack.len() < k {
+            stack.push(item);
+        }
+    }
+    Some(stack)
+}
+
+/// The digits as a decimal string, e.g. `[4, 0, 2]` as `"402"`.
+pub fn to_decimal(digits: &[u8]) -> String {
+    digits.iter().map(|&d| char::from(b'0' + d)).collect()
+}
+
+/// The digits as a number, zero for no digits.
+///
+/// # Panics
+///
+/// If any digit is 10 or more.
+pub fn to_biguint(digits: &[u8]) -> BigUint {
+    BigUint::from_radix_be(digits, 10).expect("digits must be below 10")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{largest_subsequence, parse_digits, to_biguint, to_decimal};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn parses_digits() {
+        assert_eq!(parse_digits("0907"), Some(vec![0, 9, 0, 7]));
+        assert_eq!(parse_digits(""), Some(vec![]));
+        assert_eq!(parse_digits("12a"), None);
+        assert_eq!(parse_digits("-1"), None);
+    }
+
+    #[test]
+    fn picks_largest_subsequences() {
+        let digits = parse_digits("818181911112111").unwrap();
+        let largest = |k| largest_subsequence(&digits, k).map(|d| to_decimal(&d));
+        assert_eq!(largest(2).as_deref(), Some("92"));
+        assert_eq!(largest(12).as_deref(), Some("888911112111"));
+        assert_eq!(largest(15).as_deref(), Some("818181911112111"));
+        assert_eq!(largest(0).as_deref(), Some(""));
+        assert_eq!(largest(16), None);
+
+        // Equal items are kept, not traded for later ones
+        assert_eq!(largest_subsequence(&[3, 3, 1, 3], 3), Some(vec![3, 3, 3]));
+        assert_eq!(
+            largest_subsequence(&['b', 'a', 'c'], 2),
+            Some(vec!['b', 'c'])
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        // Every subsequence of every 8-digit sequence over a small alphabet
+        for seed in 0..3u32.pow(8) {
+            let digits: Vec<u8> = (0..8).map(|i| (seed / 3u32.pow(i) % 3) as u8).collect();
+            for k in 0..=digits.len() {
+                let best = (0..1u32 << digits.len())
+                    .filter(|mask| mask.count_ones() as usize == k)
+                    .map(|mask| {
+                        let picked = digits
+                            .iter()
+                            .enumerate()
+                            .filter(|&(i, _)| mask >> i & 1 == 1);
+                        picked.map(|(_, &d)| d).collect::<Vec<u8>>()
+                    })
+                    .max();
+                assert_eq!(largest_subsequence(&digits, k), best, "{digits:?} {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn converts_long_numbers() {
+        let digits = parse_digits(&"9".repeat(40)).unwrap();
+        assert_eq!(to_decimal(&digits), "9".repeat(40));
+        assert_eq!(
+            to_biguint(&digits),
+            BigUint::from(10u8).pow(40) - BigUint::from(1u8)
+        );
+        assert_eq!(to_biguint(&[0, 0, 7]), BigUint::from(7u8));
+        assert_eq!(to_biguint(&[]), BigUint::ZERO);
+    }
+}
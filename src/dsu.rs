@@ -0,0 +1,377 @@
+//! Disjoint-set (union-find) structures.
+//!
+//! - [`DisjointSet`] for dense `usize` keys, with path compression and union by size.
+//! - [`KeyedDisjointSet`] for arbitrary hashable keys, which are interned on first use.
+//! - [`RollbackDisjointSet`] which can undo unions back to a snapshot, e.g. for offline queries.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A union-find over the elements `0..len`.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    /// Adds a new singleton set and returns its element.
+    pub fn push(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.size.push(1);
+        self.components += 1;
+        x
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The number of disjoint sets.
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Finds the representative of the set containing `x`.
+    /// Iterative, so long parent chains cannot overflow the stack.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Path compression: point every node on the path directly at the root.
+        let mut node = x;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+
+        root
+    }
+
+    /// Merges the sets containing `x` and `y`. Returns `false` if they were already joined.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut root_x, mut root_y) = (self.find(x), self.find(y));
+        if root_x == root_y {
+            return false;
+        }
+
+        // Union by size: attach the smaller tree below the larger one.
+        if self.size[root_x] < self.size[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        self.parent[root_y] = root_x;
+        self.size[root_x] += self.size[root_y];
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    /// The size of the set containing `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The sizes of all sets, in order of their representatives.
+    pub fn component_sizes(&mut self) -> Vec<usize> {
+        let mut sizes = Vec::with_capacity(self.components);
+        for x in 0..self.len() {
+            if self.find(x) == x {
+                sizes.push(self.size[x]);
+            }
+        }
+        sizes
+    }
+
+    /// The elements of every set, each in ascending order.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = HashMap::new();
+        let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let index = *index_of_root.entry(root).or_insert_with(|| {
+                components.push(Vec::with_capacity(self.size[root]));
+                components.len() - 1
+            });
+            components[index].push(x);
+        }
+
+        components
+    }
+}
+
+/// A union-find over arbitrary hashable keys. Keys are added on first use.
+#[derive(Clone, Debug)]
+pub struct KeyedDisjointSet<K> {
+    ids: HashMap<K, usize>,
+    keys: Vec<K>,
+    sets: DisjointSet,
+}
+
+impl<K> Default for KeyedDisjointSet<K> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+            sets: DisjointSet::new(0),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> KeyedDisjointSet<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `key` as a singleton set if it is not known yet, and returns its dense id.
+    pub fn insert(&mut self, key: K) -> usize {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.sets.push();
+        self.ids.insert(key.clone(), id);
+        self.keys.push(key);
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.ids.contains_key(key)
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.sets.component_count()
+    }
+
+    /// Finds the representative key of the set containing `key`, if `key` is known.
+    pub fn find(&mut self, key: &K) -> Option<&K> {
+        let id = *self.ids.get(key)?;
+        let root = self.sets.find(id);
+        Some(&self.keys[root])
+    }
+
+    /// Merges the sets containing `x` and `y`, adding either key if needed.
+    /// Returns `false` if they were already joined.
+    pub fn union(&mut self, x: K, y: K) -> bool {
+        let (x, y) = (self.insert(x), self.insert(y));
+        self.sets.union(x, y)
+    }
+
+    /// Whether both keys are known and in the same set.
+    pub fn same(&mut self, x: &K, y: &K) -> bool {
+        match (self.ids.get(x), self.ids.get(y)) {
+            (Some(&x), Some(&y)) => self.sets.same(x, y),
+            _ => false,
+        }
+    }
+
+    /// The size of the set containing `key`, or `0` if `key` is unknown.
+    pub fn size_of(&mut self, key: &K) -> usize {
+        self.ids.get(key).map_or(0, |&id| self.sets.size_of(id))
+    }
+
+    pub fn component_sizes(&mut self) -> Vec<usize> {
+        self.sets.component_sizes()
+    }
+
+    /// The keys of every set, each in insertion order.
+    pub fn components(&mut self) -> Vec<Vec<&K>> {
+        self.sets
+            .components()
+            .into_iter()
+            .map(|ids| ids.into_iter().map(|id| &self.keys[id]).collect())
+            .collect()
+    }
+}
+
+/// A union-find over `0..len` that can undo unions.
+///
+/// It skips path compression so that every union changes exactly one parent pointer,
+/// which keeps `find` at `O(log n)` through union by size.
+#[derive(Clone, Debug)]
+pub struct RollbackDisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+    /// The roots attached by each successful union, `(child, parent)`.
+    history: Vec<(usize, usize)>,
+}
+
+impl RollbackDisjointSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut root_x, mut root_y) = (self.find(x), self.find(y));
+        if root_x == root_y {
+            return false;
+        }
+
+        if self.size[root_x] < self.size[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        self.parent[root_y] = root_x;
+        self.size[root_x] += self.size[root_y];
+        self.components -= 1;
+        self.history.push((root_y, root_x));
+        true
+    }
+
+    pub fn same(&self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn size_of(&self, x: usize) -> usize {
+        self.size[self.find(x)]
+    }
+
+    /// A marker for the current state, to be passed to [`RollbackDisjointSet::rollback`].
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes all unions performed since `snapshot` was taken.
+    ///
+    /// # Panics
+    /// If the state was already rolled back past `snapshot`.
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(
+            snapshot <= self.history.len(),
+            "cannot roll back to a snapshot that was already undone"
+        );
+        while self.history.len() > snapshot {
+            let (child, parent) = self.history.pop().unwrap();
+            self.parent[child] = child;
+            self.size[parent] -= self.size[child];
+            self.components += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisjointSet, KeyedDisjointSet, RollbackDisjointSet};
+
+    #[test]
+    fn unions_and_finds() {
+        let mut sets = DisjointSet::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+
+        assert!(sets.same(0, 3));
+        assert!(!sets.same(0, 4));
+        assert_eq!(sets.size_of(2), 4);
+        assert_eq!(sets.component_count(), 3);
+
+        let mut sizes = sets.component_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 4]);
+        assert_eq!(sets.components(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn handles_long_chains() {
+        let n = 1_000_000;
+        let mut sets = DisjointSet::new(n);
+        for x in 1..n {
+            sets.union(x - 1, x);
+        }
+        assert_eq!(sets.size_of(n - 1), n);
+        assert_eq!(sets.component_count(), 1);
+    }
+
+    #[test]
+    fn supports_hashable_keys() {
+        let mut sets = KeyedDisjointSet::new();
+        sets.union("a", "b");
+        sets.union("c", "d");
+        sets.insert("e");
+
+        assert!(sets.same(&"a", &"b"));
+        assert!(!sets.same(&"a", &"c"));
+        assert!(!sets.same(&"a", &"z"));
+        assert_eq!(sets.size_of(&"d"), 2);
+        assert_eq!(sets.find(&"z"), None);
+        assert_eq!(sets.component_count(), 3);
+        assert_eq!(
+            sets.components(),
+            vec![vec![&"a", &"b"], vec![&"c", &"d"], vec![&"e"]]
+        );
+    }
+
+    #[test]
+    fn rolls_back_unions() {
+        let mut sets = RollbackDisjointSet::new(4);
+        sets.union(0, 1);
+        let snapshot = sets.snapshot();
+
+        sets.union(1, 2);
+        sets.union(2, 3);
+        assert_eq!(sets.component_count(), 1);
+        assert_eq!(sets.size_of(0), 4);
+
+        sets.rollback(snapshot);
+        assert_eq!(sets.component_count(), 3);
+        assert!(sets.same(0, 1));
+        assert!(!sets.same(1, 2));
+        assert_eq!(sets.size_of(0), 2);
+        assert_eq!(sets.size_of(3), 1);
+    }
+}
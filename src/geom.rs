@@ -0,0 +1,679 @@
+//! Integer points in 2D and 3D, bounding boxes and polygon helpers.
+//!
+//! Polygons are slices of vertices in order; the last vertex connects back to the first.
+//! All predicates treat polygons as closed regions, so points on an edge are contained.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// The squared Euclidean distance, which is exact and orders points like the real distance.
+    pub fn euclidean_squared(self, other: Self) -> i64 {
+        let d = self - other;
+        d.x * d.x + d.y * d.y
+    }
+
+    pub fn euclidean(self, other: Self) -> f64 {
+        (self.euclidean_squared(other) as f64).sqrt()
+    }
+
+    /// The cross product of `a - self` and `b - self`. Positive if `self -> a -> b` turns counter-clockwise.
+    pub fn cross(self, a: Self, b: Self) -> i64 {
+        let (u, v) = (a - self, b - self);
+        u.x * v.y - u.y * v.x
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.x.max(other.x), self.y.max(other.y))
+    }
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+
+    /// The squared Euclidean distance, which is exact and orders points like the real distance.
+    pub fn euclidean_squared(self, other: Self) -> i64 {
+        let d = self - other;
+        d.x * d.x + d.y * d.y + d.z * d.z
+    }
+
+    pub fn euclidean(self, other: Self) -> f64 {
+        (self.euclidean_squared(other) as f64).sqrt()
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+}
+
+macro_rules! impl_ops {
+    ($t:ident, $($field:ident),+) => {
+        impl Add for $t {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl Sub for $t {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl Mul<i64> for $t {
+            type Output = Self;
+
+            fn mul(self, rhs: i64) -> Self {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl Neg for $t {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $t {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $t {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        /// Parses comma-separated coordinates, e.g. `1,-2`.
+        impl FromStr for $t {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut parts = s.split(',').map(|x| {
+                    x.trim()
+                        .parse::<i64>()
+                        .map_err(|e| format!("invalid coordinate `{x}`: {e}"))
+                });
+                let point = Self {
+                    $($field: parts.next().ok_or_else(|| format!("too few coordinates in `{s}`"))??),+
+                };
+                match parts.next() {
+                    Some(_) => Err(format!("too many coordinates in `{s}`")),
+                    None => Ok(point),
+                }
+            }
+        }
+    };
+}
+
+impl_ops!(Point2, x, y);
+impl_ops!(Point3, x, y, z);
+
+impl Display for Point2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Display for Point3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+/// The smallest axis-aligned box containing a set of points, with inclusive corners.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoundingBox<P> {
+    pub min: P,
+    pub max: P,
+}
+
+macro_rules! impl_bounding_box {
+    ($t:ident, $($field:ident),+) => {
+        impl BoundingBox<$t> {
+            /// The box spanned by two opposite corners.
+            pub fn from_corners(a: $t, b: $t) -> Self {
+                Self { min: a.min(b), max: a.max(b) }
+            }
+
+            /// The bounding box of `points`, or `None` if there are none.
+            pub fn from_points(points: impl IntoIterator<Item = $t>) -> Option<Self> {
+                let mut points = points.into_iter();
+                let first = points.next()?;
+                Some(points.fold(Self { min: first, max: first }, |bb, p| bb.including(p)))
+            }
+
+            /// The box grown to contain `point`.
+            pub fn including(self, point: $t) -> Self {
+                Self { min: self.min.min(point), max: self.max.max(point) }
+            }
+
+            pub fn contains(&self, point: $t) -> bool {
+                $(self.min.$field <= point.$field && point.$field <= self.max.$field)&&+
+            }
+
+            /// Whether both boxes share at least one point.
+            pub fn intersects(&self, other: &Self) -> bool {
+                $(self.min.$field <= other.max.$field && other.min.$field <= self.max.$field)&&+
+            }
+
+            /// The number of integer points in the box, i.e. the number of grid cells it covers.
+            pub fn point_count(&self) -> u64 {
+                1 $(* (self.max.$field.abs_diff(self.min.$field) + 1))+
+            }
+        }
+    };
+}
+
+impl_bounding_box!(Point2, x, y);
+impl_bounding_box!(Point3, x, y, z);
+
+impl BoundingBox<Point2> {
+    pub fn width(&self) -> u64 {
+        self.max.x.abs_diff(self.min.x)
+    }
+
+    pub fn height(&self) -> u64 {
+        self.max.y.abs_diff(self.min.y)
+    }
+
+    /// The area of the box as a continuous region.
+    pub fn area(&self) -> u64 {
+        self.width() * self.height()
+    }
+}
+
+/// Twice the signed area of a polygon: positive for counter-clockwise vertex order (shoelace formula).
+pub fn polygon_double_area(polygon: &[Point2]) -> i64 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum()
+}
+
+/// The area enclosed by a polygon.
+pub fn polygon_area(polygon: &[Point2]) -> f64 {
+    polygon_double_area(polygon).abs() as f64 / 2.0
+}
+
+/// The edges of a polygon, including the closing edge from the last vertex to the first.
+pub fn polygon_edges(polygon: &[Point2]) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a, b))
+}
+
+/// Where a point lies relative to a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+fn on_segment(p: Point2, a: Point2, b: Point2) -> bool {
+    a.cross(b, p) == 0 && BoundingBox::<Point2>::from_corners(a, b).contains(p)
+}
+
+/// Locates `point` relative to `polygon` by casting a ray towards positive `x`.
+pub fn locate_point(point: Point2, polygon: &[Point2]) -> Location {
+    let mut inside = false;
+
+    for (a, b) in polygon_edges(polygon) {
+        if on_segment(point, a, b) {
+            return Location::Boundary;
+        }
+        if (a.y > point.y) != (b.y > point.y) {
+            // The edge crosses the ray's line; count it if the crossing is right of `point`.
+            // The sign of the cross product tells on which side of the edge `point` lies.
+            let side = a.cross(b, point);
+            if (side > 0) == (b.y > a.y) {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Whether `point` is inside of or on the boundary of `polygon`.
+pub fn point_in_polygon(point: Point2, polygon: &[Point2]) -> bool {
+    locate_point(point, polygon) != Location::Outside
+}
+
+/// Whether the closed rectangle spanned by corners `a` and `b` lies within an orthogonal polygon,
+/// i.e. a polygon whose edges are all horizontal or vertical.
+pub fn orthogonal_polygon_contains_rect(polygon: &[Point2], a: Point2, b: Point2) -> bool {
+    let rect = BoundingBox::<Point2>::from_corners(a, b);
+
+    // Work with doubled coordinates so that midpoints stay integral.
+    let doubled: Vec<Point2> = polygon.iter().map(|&p| p * 2).collect();
+    let (lo, hi) = (rect.min * 2, rect.max * 2);
+
+    // Every edge cuts the rectangle into pieces along a line. Between two consecutive cut lines
+    // the polygon boundary does not enter the rectangle, so each piece is either inside or outside,
+    // and checking one point per piece and per cut line decides the whole rectangle.
+    let mut xs = vec![lo.x, hi.x];
+    let mut ys = vec![lo.y, hi.y];
+    for (p, q) in polygon_edges(&doubled) {
+        debug_assert!(p.x == q.x || p.y == q.y, "polygon must be orthogonal");
+        if p.x == q.x && lo.x < p.x && p.x < hi.x {
+            xs.push(p.x);
+        }
+        if p.y == q.y && lo.y < p.y && p.y < hi.y {
+            ys.push(p.y);
+        }
+    }
+
+    // Also cut at polygon vertices so that corners touching the rectangle are sampled.
+    for p in &doubled {
+        if lo.x < p.x && p.x < hi.x {
+            xs.push(p.x);
+        }
+        if lo.y < p.y && p.y < hi.y {
+            ys.push(p.y);
+        }
+    }
+
+    let samples = |mut cuts: Vec<i64>| {
+        cuts.sort_unstable();
+        cuts.dedup();
+        let midpoints: Vec<i64> = cuts.windows(2).map(|w| (w[0] + w[1]) / 2).collect();
+        cuts.extend(midpoints);
+        cuts
+    };
+
+    let (xs, ys) = (samples(xs), samples(ys));
+    xs.iter().all(|&x| {
+        ys.iter()
+            .all(|&y| point_in_polygon(Point2::new(x, y), &doubled))
+    })
+}
+
+/// For each point `(p, q)`, the positions of the nearest segments below and above `q` that cross
+/// it, i.e. segments `(q', lo, hi)` with `lo < p < hi`. A sweep over `p`, in `O(n log n)`.
+fn nearest_crossings(
+    segments: &[(i64, i64, i64)],
+    points: &[(i64, i64)],
+) -> Vec<(Option<i64>, Option<i64>)> {
+    let by_start: Vec<_> = segments.iter().sorted_unstable_by_key(|s| s.1).collect();
+    let by_end: Vec<_> = segments.iter().sorted_unstable_by_key(|s| s.2).collect();
+    let order = (0..points.len()).sorted_unstable_by_key(|&i| points[i].0);
+
+    let mut active = BTreeSet::new();
+    let (mut started, mut ended) = (0, 0);
+    let mut nearest = vec![(None, None); points.len()];
+    for i in order {
+        let (p, q) = points[i];
+        while started < by_start.len() && by_start[started].1 < p {
+            active.insert(*by_start[started]);
+            started += 1;
+        }
+        while ended < by_end.len() && by_end[ended].2 <= p {
+            active.remove(by_end[ended]);
+            ended += 1;
+        }
+        let below = active.range(..(q, i64::MIN, i64::MIN)).next_back();
+        let above = active.range((q + 1, i64::MIN, i64::MIN)..).next();
+        nearest[i] = (below.map(|s| s.0), above.map(|s| s.0));
+    }
+    nearest
+}
+
+/// An orthogonal polygon, indexed for fast containment checks of rectangles whose corners are
+/// two of its vertices.
+#[derive(Clone, Debug)]
+pub struct OrthogonalPolygon {
+    vertices: Vec<Point2>,
+    /// Per vertex, how far the polygon extends from it towards `+x`, `-x`, `+y` and `-y`.
+    reach: Vec<[i64; 4]>,
+}
+
+impl OrthogonalPolygon {
+    /// Indexes a simple polygon in `O(n log n)`.
+    ///
+    /// # Panics
+    /// If an edge is neither horizontal nor vertical.
+    pub fn new(vertices: Vec<Point2>) -> Self {
+        let mut vertical = vec![];
+        let mut horizontal = vec![];
+        for (a, b) in polygon_edges(&vertices) {
+            assert!(a.x == b.x || a.y == b.y, "polygon must be orthogonal");
+            if a.x == b.x {
+                vertical.push((a.x, a.y.min(b.y), a.y.max(b.y)));
+            } else {
+                horizontal.push((a.y, a.x.min(b.x), a.x.max(b.x)));
+            }
+        }
+
+        // Which directions lead into the polygon at each vertex, as for `reach`. The interior is
+        // left of every edge for counter-clockwise polygons, right otherwise.
+        let orientation = polygon_double_area(&vertices).signum();
+        let n = vertices.len();
+        let inward: Vec<[bool; 4]> = (0..n)
+            .map(|i| {
+                let (prev, v, next) = (
+                    vertices[(i + n - 1) % n],
+                    vertices[i],
+                    vertices[(i + 1) % n],
+                );
+                let (incoming, outgoing) = (v - prev, next - v);
+                let left_of = |edge: Point2, dir: Point2| {
+                    (edge.x * dir.y - edge.y * dir.x).signum() == orientation
+                };
+                let turn = (incoming.x * outgoing.y - incoming.y * outgoing.x).signum();
+                let inside = |dx, dy| {
+                    let dir = Point2::new(dx, dy);
+                    match turn * orientation {
+                        // Convex corner: inside both edges
+                        1 => left_of(incoming, dir) && left_of(outgoing, dir),
+                        // Reflex corner: inside either edge
+                        -1 => left_of(incoming, dir) || left_of(outgoing, dir),
+                        // Straight or degenerate: one edge decides
+                        _ => left_of(
+                            if outgoing == Point2::ORIGIN {
+                                incoming
+                            } else {
+                                outgoing
+                            },
+                            dir,
+                        ),
+                    }
+                };
+                // A direction leads inside if a quadrant next to it does
+                [
+                    inside(1, 1) || inside(1, -1),
+                    inside(-1, 1) || inside(-1, -1),
+                    inside(1, 1) || inside(-1, 1),
+                    inside(1, -1) || inside(-1, -1),
+                ]
+            })
+            .collect();
+        let mut reach: Vec<[i64; 4]> = inward
+            .iter()
+            .map(|dirs| dirs.map(|inside| if inside { i64::MAX } else { 0 }))
+            .collect();
+
+        // A ray along an axis leaves the polygon where it crosses an edge, or where it meets a
+        // vertex that doesn't lead on in its direction.
+        let xs: Vec<_> = vertices.iter().map(|v| (v.y, v.x)).collect();
+        let ys: Vec<_> = vertices.iter().map(|v| (v.x, v.y)).collect();
+        for (axis, segments, points) in [(0, &vertical, &xs), (2, &horizontal, &ys)] {
+            let crossings = nearest_crossings(segments, points);
+            for (i, (below, above)) in crossings.into_iter().enumerate() {
+                let q = points[i].1;
+                if let Some(above) = above {
+                    reach[i][axis] = reach[i][axis].min(above - q);
+                }
+                if let Some(below) = below {
+                    reach[i][axis + 1] = reach[i][axis + 1].min(q - below);
+                }
+            }
+
+            let lines = (0..n).sorted_unstable_by_key(|&i| points[i]).collect_vec();
+            for line in lines.chunk_by(|&i, &j| points[i].0 == points[j].0) {
+                let mut stop = None;
+                for &i in line.iter().rev() {
+                    if let Some(stop) = stop {
+                        reach[i][axis] = reach[i][axis].min(stop - points[i].1);
+                    }
+                    if !inward[i][axis] {
+                        stop = Some(points[i].1);
+                    }
+                }
+                stop = None;
+                for &i in line {
+                    if let Some(stop) = stop {
+                        reach[i][axis + 1] = reach[i][axis + 1].min(points[i].1 - stop);
+                    }
+                    if !inward[i][axis + 1] {
+                        stop = Some(points[i].1);
+                    }
+                }
+            }
+        }
+
+        Self { vertices, reach }
+    }
+
+    pub fn vertices(&self) -> &[Point2] {
+        &self.vertices
+    }
+
+    /// Whether the closed rectangle spanned by the vertices `i` and `j` lies within the polygon.
+    /// Takes `O(1)`: without holes, the rectangle is inside if its edges are.
+    pub fn contains_vertex_rect(&self, i: usize, j: usize) -> bool {
+        let d = self.vertices[j] - self.vertices[i];
+        let edges_inside = |k: usize, dx: i64, dy: i64| {
+            let [right, left, up, down] = self.reach[k];
+            (if dx >= 0 { right } else { left }) >= dx.abs()
+                && (if dy >= 0 { up } else { down }) >= dy.abs()
+        };
+        edges_inside(i, d.x, d.y) && edges_inside(j, -d.x, -d.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BoundingBox, Location, OrthogonalPolygon, Point2, Point3, locate_point,
+        orthogonal_polygon_contains_rect, polygon_area, polygon_double_area,
+    };
+
+    fn p(x: i64, y: i64) -> Point2 {
+        Point2::new(x, y)
+    }
+
+    /// An L-shaped polygon: a 4x4 square with the top-right 2x2 quadrant removed.
+    fn l_shape() -> Vec<Point2> {
+        vec![p(0, 0), p(4, 0), p(4, 2), p(2, 2), p(2, 4), p(0, 4)]
+    }
+
+    #[test]
+    fn computes_metrics() {
+        let (a, b) = (Point3::new(1, 2, 3), Point3::new(4, -2, 3));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(a.euclidean_squared(b), 25);
+        assert_eq!(a.euclidean(b), 5.0);
+        assert_eq!(a + b, Point3::new(5, 0, 6));
+        assert_eq!((a - b) * 2, Point3::new(-6, 8, 0));
+        assert_eq!(p(1, 1).manhattan(p(-2, 3)), 5);
+    }
+
+    #[test]
+    fn parses_points() {
+        assert_eq!("7,-1".parse::<Point2>(), Ok(p(7, -1)));
+        assert_eq!("1, 2, 3".parse::<Point3>(), Ok(Point3::new(1, 2, 3)));
+        assert!("1,2,3".parse::<Point2>().is_err());
+        assert!("1".parse::<Point2>().is_err());
+        assert_eq!(Point3::new(1, 2, 3).to_string(), "1,2,3");
+    }
+
+    #[test]
+    fn builds_bounding_boxes() {
+        let bb = BoundingBox::<Point2>::from_points([p(3, 1), p(-1, 4), p(2, 2)]).unwrap();
+        assert_eq!((bb.min, bb.max), (p(-1, 1), p(3, 4)));
+        assert_eq!(bb.point_count(), 20);
+        assert_eq!(bb.area(), 12);
+        assert!(bb.contains(p(0, 1)));
+        assert!(!bb.contains(p(0, 0)));
+        assert_eq!(
+            BoundingBox::<Point2>::from_corners(p(2, 5), p(11, 7)).point_count(),
+            30
+        );
+    }
+
+    #[test]
+    fn computes_polygon_areas() {
+        assert_eq!(polygon_double_area(&l_shape()), 24);
+        let mut clockwise = l_shape();
+        clockwise.reverse();
+        assert_eq!(polygon_double_area(&clockwise), -24);
+        assert_eq!(polygon_area(&clockwise), 12.0);
+    }
+
+    #[test]
+    fn locates_points() {
+        let polygon = l_shape();
+        assert_eq!(locate_point(p(1, 1), &polygon), Location::Inside);
+        assert_eq!(locate_point(p(1, 3), &polygon), Location::Inside);
+        assert_eq!(locate_point(p(3, 3), &polygon), Location::Outside);
+        assert_eq!(locate_point(p(4, 1), &polygon), Location::Boundary);
+        assert_eq!(locate_point(p(2, 3), &polygon), Location::Boundary);
+        assert_eq!(locate_point(p(5, 0), &polygon), Location::Outside);
+        assert_eq!(locate_point(p(-1, 2), &polygon), Location::Outside);
+    }
+
+    #[test]
+    fn checks_rectangle_containment() {
+        let polygon = l_shape();
+        assert!(orthogonal_polygon_contains_rect(&polygon, p(0, 0), p(4, 2)));
+        assert!(orthogonal_polygon_contains_rect(&polygon, p(0, 0), p(2, 4)));
+        assert!(orthogonal_polygon_contains_rect(&polygon, p(2, 2), p(4, 2)));
+        assert!(!orthogonal_polygon_contains_rect(
+            &polygon,
+            p(0, 0),
+            p(4, 4)
+        ));
+        assert!(!orthogonal_polygon_contains_rect(
+            &polygon,
+            p(1, 1),
+            p(3, 3)
+        ));
+        assert!(!orthogonal_polygon_contains_rect(
+            &polygon,
+            p(3, 3),
+            p(3, 3)
+        ));
+
+        // A U shape whose slot is a single unit wide: the rectangle across the slot is not contained.
+        let u_shape = vec![
+            p(0, 0),
+            p(7, 0),
+            p(7, 5),
+            p(4, 5),
+            p(4, 2),
+            p(3, 2),
+            p(3, 5),
+            p(0, 5),
+        ];
+        assert!(orthogonal_polygon_contains_rect(&u_shape, p(0, 0), p(7, 2)));
+        assert!(!orthogonal_polygon_contains_rect(
+            &u_shape,
+            p(0, 0),
+            p(7, 3)
+        ));
+    }
+
+    #[test]
+    fn indexes_orthogonal_polygons() {
+        // A comb with three teeth, in both orientations, and the L shape.
+        let comb = vec![
+            p(0, 0),
+            p(9, 0),
+            p(9, 6),
+            p(7, 6),
+            p(7, 2),
+            p(5, 2),
+            p(5, 6),
+            p(3, 6),
+            p(3, 2),
+            p(1, 2),
+            p(1, 6),
+            p(0, 6),
+        ];
+        let reversed: Vec<Point2> = comb.iter().rev().copied().collect();
+
+        for vertices in [comb, reversed, l_shape()] {
+            let polygon = OrthogonalPolygon::new(vertices.clone());
+            for i in 0..vertices.len() {
+                for j in 0..vertices.len() {
+                    assert_eq!(
+                        polygon.contains_vertex_rect(i, j),
+                        orthogonal_polygon_contains_rect(&vertices, vertices[i], vertices[j]),
+                        "rectangle from {} to {}",
+                        vertices[i],
+                        vertices[j]
+                    );
+                }
+            }
+        }
+    }
+}
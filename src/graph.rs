@@ -0,0 +1,527 @@
+//! A directed graph with interned node names and common algorithms:
+//! path counting and enumeration in DAGs, topological ordering with cycle detection, BFS, Dijkstra and A*.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::ops::AddAssign;
+
+/// A dense node index, assigned in insertion order.
+pub type NodeId = usize;
+
+/// The most required nodes that path counting supports, see [`Graph::path_counts`].
+pub const MAX_REQUIRED: usize = 16;
+
+/// An error which is returned when an algorithm that requires a DAG encounters a cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    /// The nodes on the cycle in edge order. The last node has an edge back to the first.
+    pub cycle: Vec<NodeId>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "graph contains a cycle of {} node(s)", self.cycle.len())
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// A directed graph with weighted edges. Nodes are identified by `K` and interned to [`NodeId`]s.
+#[derive(Clone, Debug)]
+pub struct Graph<K> {
+    ids: HashMap<K, NodeId>,
+    names: Vec<K>,
+    edges: Vec<Vec<(NodeId, u64)>>,
+}
+
+impl<K> Default for Graph<K> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            names: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> Graph<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `name` as a node if it is not known yet, and returns its id.
+    pub fn add_node(&mut self, name: K) -> NodeId {
+        if let Some(&id) = self.ids.get(&name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.ids.insert(name.clone(), id);
+        self.names.push(name);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    /// Adds an edge of weight `1`, creating both nodes if needed.
+    pub fn add_edge(&mut self, from: K, to: K) {
+        self.add_weighted_edge(from, to, 1);
+    }
+
+    /// Adds an edge, creating both nodes if needed.
+    pub fn add_weighted_edge(&mut self, from: K, to: K, weight: u64) {
+        let (from, to) = (self.add_node(from), self.add_node(to));
+        self.edges[from].push((to, weight));
+    }
+
+    /// The id of `name`, if it is a node of the graph.
+    pub fn id(&self, name: &K) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+}
+
+impl<K> Graph<K> {
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, id: NodeId) -> &K {
+        &self.names[id]
+    }
+
+    /// The outgoing edges of `id` as `(target, weight)` pairs.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, u64)] {
+        &self.edges[id]
+    }
+
+    /// The targets of the outgoing edges of `id`.
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id].iter().map(|&(to, _)| to)
+    }
+
+    /// Orders all nodes so that every edge points forward.
+    pub fn topological_order(&self) -> Result<Vec<NodeId>, CycleError> {
+        self.topological_order_from(0..self.len())
+    }
+
+    /// Orders the nodes reachable from `roots` so that every edge points forward.
+    /// Cycles outside of the reachable part of the graph are ignored.
+    pub fn topological_order_from(
+        &self,
+        roots: impl IntoIterator<Item = NodeId>,
+    ) -> Result<Vec<NodeId>, CycleError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            Active,
+            Done,
+        }
+
+        let mut state = vec![State::New; self.len()];
+        let mut post_order = Vec::with_capacity(self.len());
+        // (node, index of the next edge to follow)
+        let mut stack: Vec<(NodeId, usize)> = Vec::new();
+
+        for root in roots {
+            if state[root] != State::New {
+                continue;
+            }
+            state[root] = State::Active;
+            stack.push((root, 0));
+
+            while let Some(&mut (node, ref mut next_edge)) = stack.last_mut() {
+                if let Some(&(next, _)) = self.edges[node].get(*next_edge) {
+                    *next_edge += 1;
+                    match state[next] {
+                        State::New => {
+                            state[next] = State::Active;
+                            stack.push((next, 0));
+                        }
+                        State::Active => {
+                            // `next` is on the stack, so the cycle is the stack from `next` onwards.
+                            let start = stack.iter().position(|&(x, _)| x == next).unwrap();
+                            return Err(CycleError {
+                                cycle: stack[start..].iter().map(|&(x, _)| x).collect(),
+                            });
+                        }
+                        State::Done => {}
+                    }
+                } else {
+                    state[node] = State::Done;
+                    post_order.push(node);
+                    stack.pop();
+                }
+            }
+        }
+
+        post_order.reverse();
+        Ok(post_order)
+    }
+
+    /// Finds a cycle reachable from any node, if there is one.
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        self.topological_order().err().map(|e| e.cycle)
+    }
+
+    /// Counts the paths from `from` to `to` that visit every node in `required` in any order.
+    /// Paths end as soon as they reach `to`.
+    ///
+    /// Runs in `O((V + E) * 2^k)` for `k` required nodes and fails if a cycle is reachable from `from`.
+    ///
+    /// # Panics
+    /// If more than [`MAX_REQUIRED`] required nodes are given.
+    pub fn count_paths(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        required: &[NodeId],
+    ) -> Result<u64, CycleError> {
+        let counts = self.path_counts::<u64>(from, to, required, &[])?;
+        Ok(*counts.count())
+    }
+
+    /// Counts the paths from `from` to `to` that visit every node in `required` and none in
+    /// `forbidden`, as any integer type `T`, e.g. a big integer for counts beyond `u64`.
+    /// The counts also lead the way to concrete paths, see [`PathCounts::paths`].
+    ///
+    /// Runs in `O((V + E) * 2^k)` for `k` required nodes and fails if a cycle is reachable from `from`.
+    ///
+    /// # Panics
+    /// If more than [`MAX_REQUIRED`] required nodes are given.
+    pub fn path_counts<T>(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        required: &[NodeId],
+        forbidden: &[NodeId],
+    ) -> Result<PathCounts<'_, K, T>, CycleError>
+    where
+        T: Clone + PartialEq + From<u8> + for<'a> AddAssign<&'a T>,
+    {
+        assert!(
+            required.len() <= MAX_REQUIRED,
+            "at most {MAX_REQUIRED} required nodes are supported"
+        );
+
+        let masks = 1 << required.len();
+        let zero = T::from(0);
+        let mut counts = vec![zero.clone(); self.len() * masks];
+        let mut is_forbidden = vec![false; self.len()];
+        for &node in forbidden {
+            is_forbidden[node] = true;
+        }
+
+        // counts[node * masks + m]: paths from `node` to `to` that visit exactly the required nodes in `m`.
+        let mut row = vec![zero.clone(); masks];
+        for node in self.topological_order_from([from])?.into_iter().rev() {
+            if is_forbidden[node] {
+                continue;
+            }
+            let own = required_mask(required, node);
+            if node == to {
+                counts[node * masks + own] = T::from(1);
+                continue;
+            }
+            for next in self.neighbors(node) {
+                for m in 0..masks {
+                    let count = &counts[next * masks + m];
+                    if *count != zero {
+                        row[m | own] += count;
+                    }
+                }
+            }
+            // The node's own counts are still zero, which leaves `row` zeroed for the next node
+            counts[node * masks..(node + 1) * masks].swap_with_slice(&mut row);
+        }
+
+        Ok(PathCounts {
+            graph: self,
+            from,
+            to,
+            required: required.to_vec(),
+            counts,
+        })
+    }
+
+    /// The number of edges on a shortest path from `start` to every node, ignoring weights.
+    pub fn bfs(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut dist = vec![None; self.len()];
+        let mut queue = VecDeque::from([start]);
+        dist[start] = Some(0);
+
+        while let Some(node) = queue.pop_front() {
+            let d = dist[node].unwrap();
+            for next in self.neighbors(node) {
+                if dist[next].is_none() {
+                    dist[next] = Some(d + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// The weight of a shortest path from `start` to every node.
+    pub fn dijkstra(&self, start: NodeId) -> Vec<Option<u64>> {
+        let mut dist = vec![None; self.len()];
+        let mut heap = BinaryHeap::from([Reverse((0, start))]);
+
+        while let Some(Reverse((d, node))) = heap.pop() {
+            if dist[node].is_some() {
+                continue;
+            }
+            dist[node] = Some(d);
+            for &(next, weight) in self.edges(node) {
+                if dist[next].is_none() {
+                    heap.push(Reverse((d + weight, next)));
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Finds a shortest path from `start` to `goal` with A*, returning its weight and nodes.
+    /// `heuristic` must never overestimate the remaining distance to `goal`.
+    pub fn astar(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        heuristic: impl Fn(NodeId) -> u64,
+    ) -> Option<(u64, Vec<NodeId>)> {
+        let mut dist = vec![u64::MAX; self.len()];
+        let mut previous = vec![None; self.len()];
+        let mut heap = BinaryHeap::from([Reverse((heuristic(start), 0, start))]);
+        dist[start] = 0;
+
+        while let Some(Reverse((_, d, node))) = heap.pop() {
+            if node == goal {
+                let mut path = vec![goal];
+                while let Some(prev) = previous[*path.last().unwrap()] {
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some((d, path));
+            }
+            if d > dist[node] {
+                continue;
+            }
+            for &(next, weight) in self.edges(node) {
+                let next_dist = d + weight;
+                if next_dist < dist[next] {
+                    dist[next] = next_dist;
+                    previous[next] = Some(node);
+                    heap.push(Reverse((next_dist + heuristic(next), next_dist, next)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Finds a shortest path from `start` to `goal`, returning its weight and nodes.
+    pub fn shortest_path(&self, start: NodeId, goal: NodeId) -> Option<(u64, Vec<NodeId>)> {
+        self.astar(start, goal, |_| 0)
+    }
+}
+
+/// The bit of each required node equal to `node` in a mask over `required`.
+fn required_mask(required: &[NodeId], node: NodeId) -> usize {
+    required
+        .iter()
+        .enumerate()
+        .filter(|&(_, &x)| x == node)
+        .fold(0, |acc, (i, _)| acc | (1 << i))
+}
+
+/// Path counts from every node reachable from a source to a target, by the required nodes on
+/// the way, as computed by [`Graph::path_counts`].
+#[derive(Clone, Debug)]
+pub struct PathCounts<'a, K, T> {
+    graph: &'a Graph<K>,
+    from: NodeId,
+    to: NodeId,
+    required: Vec<NodeId>,
+    counts: Vec<T>,
+}
+
+impl<K, T: Clone + PartialEq + From<u8>> PathCounts<'_, K, T> {
+    fn masks(&self) -> usize {
+        1 << self.required.len()
+    }
+
+    /// The number of paths through all required nodes.
+    pub fn count(&self) -> &T {
+        &self.counts[self.from * self.masks() + self.masks() - 1]
+    }
+
+    /// Up to `limit` of the counted paths, in the order of the edges of the graph.
+    /// Only follows edges with paths behind them, so each path takes `O(length * 2^k)`.
+    pub fn paths(&self, limit: usize) -> Vec<Vec<NodeId>> {
+        let mut paths = vec![];
+        let full = self.masks() - 1;
+        let own = required_mask(&self.required, self.from);
+        if self.leads_on(self.from, own, full) {
+            self.extend(&mut vec![self.from], own, limit, &mut paths);
+        }
+        paths
+    }
+
+    /// Whether paths from `node` complete the required nodes `visited` before it.
+    fn leads_on(&self, node: NodeId, visited: usize, full: usize) -> bool {
+        let zero = T::from(0);
+        (0..self.masks())
+            .any(|m| m | visited == full && self.counts[node * self.masks() + m] != zero)
+    }
+
+    fn extend(
+        &self,
+        path: &mut Vec<NodeId>,
+        visited: usize,
+        limit: usize,
+        paths: &mut Vec<Vec<NodeId>>,
+    ) {
+        let node = *path.last().unwrap();
+        if paths.len() == limit {
+            return;
+        }
+        if node == self.to {
+            paths.push(path.clone());
+            return;
+        }
+        let full = self.masks() - 1;
+        for next in self.graph.neighbors(node) {
+            let visited = visited | required_mask(&self.required, next);
+            if self.leads_on(next, visited, full) {
+                path.push(next);
+                self.extend(path, visited, limit, paths);
+                path.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CycleError, Graph};
+
+    fn diamond() -> Graph<&'static str> {
+        let mut graph = Graph::new();
+        for (from, to) in [("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "e")] {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    #[test]
+    fn interns_nodes() {
+        let graph = diamond();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.id(&"c"), Some(2));
+        assert_eq!(graph.name(3), &"d");
+        assert_eq!(graph.id(&"z"), None);
+    }
+
+    #[test]
+    fn orders_topologically() {
+        let graph = diamond();
+        let order = graph.topological_order().unwrap();
+        let position = |name| order.iter().position(|&x| x == graph.id(&name).unwrap());
+        assert!(position("a") < position("b"));
+        assert!(position("b") < position("d"));
+        assert!(position("c") < position("d"));
+        assert!(position("d") < position("e"));
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let mut graph = diamond();
+        graph.add_edge("e", "b");
+        let cycle = graph.find_cycle().unwrap();
+        let names: Vec<_> = cycle.iter().map(|&x| *graph.name(x)).collect();
+        assert_eq!(names, vec!["b", "d", "e"]);
+
+        let (a, e) = (graph.id(&"a").unwrap(), graph.id(&"e").unwrap());
+        assert!(matches!(
+            graph.count_paths(a, e, &[]),
+            Err(CycleError { .. })
+        ));
+    }
+
+    #[test]
+    fn counts_paths_through_waypoints() {
+        let graph = diamond();
+        let id = |name| graph.id(&name).unwrap();
+        assert_eq!(graph.count_paths(id("a"), id("e"), &[]), Ok(2));
+        assert_eq!(graph.count_paths(id("a"), id("e"), &[id("b")]), Ok(1));
+        assert_eq!(
+            graph.count_paths(id("a"), id("e"), &[id("b"), id("c")]),
+            Ok(0)
+        );
+        assert_eq!(graph.count_paths(id("a"), id("d"), &[id("d")]), Ok(2));
+        assert_eq!(graph.count_paths(id("e"), id("a"), &[]), Ok(0));
+    }
+
+    #[test]
+    fn lists_paths_avoiding_nodes() {
+        let mut graph = diamond();
+        graph.add_edge("a", "d");
+        let id = |name| graph.id(&name).unwrap();
+        let names = |paths: Vec<Vec<usize>>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|path| path.iter().map(|&x| *graph.name(x)).collect())
+                .collect()
+        };
+
+        let counts = graph
+            .path_counts::<u128>(id("a"), id("e"), &[], &[id("c")])
+            .unwrap();
+        assert_eq!(*counts.count(), 2);
+        assert_eq!(names(counts.paths(10)), vec!["abde", "ade"]);
+        assert_eq!(names(counts.paths(1)), vec!["abde"]);
+
+        let counts = graph
+            .path_counts::<u128>(id("a"), id("e"), &[id("c")], &[])
+            .unwrap();
+        assert_eq!(*counts.count(), 1);
+        assert_eq!(names(counts.paths(10)), vec!["acde"]);
+
+        let counts = graph
+            .path_counts::<u128>(id("a"), id("e"), &[], &[id("d")])
+            .unwrap();
+        assert_eq!(*counts.count(), 0);
+        assert!(counts.paths(10).is_empty());
+    }
+
+    #[test]
+    fn finds_shortest_paths() {
+        let mut graph = Graph::new();
+        graph.add_weighted_edge("a", "b", 1);
+        graph.add_weighted_edge("b", "c", 1);
+        graph.add_weighted_edge("a", "c", 5);
+        graph.add_weighted_edge("c", "d", 1);
+        graph.add_node("x");
+
+        let id = |name| graph.id(&name).unwrap();
+        assert_eq!(
+            graph.bfs(id("a")),
+            vec![Some(0), Some(1), Some(1), Some(2), None]
+        );
+        assert_eq!(
+            graph.dijkstra(id("a")),
+            vec![Some(0), Some(1), Some(2), Some(3), None]
+        );
+        assert_eq!(
+            graph.shortest_path(id("a"), id("d")),
+            Some((3, vec![id("a"), id("b"), id("c"), id("d")]))
+        );
+        assert_eq!(graph.astar(id("a"), id("x"), |_| 0), None);
+    }
+}
@@ -0,0 +1,392 @@
+//! A two-dimensional grid with flat, row-major storage.
+//!
+//! Positions are `(row, col)` tuples with the origin in the top-left corner.
+
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+/// A `(row, col)` position in a [`Grid`].
+pub type Pos = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+const ALL_DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Which cells count as adjacent, e.g. for [`Grid::flood_fill`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Orthogonal and diagonal neighbors.
+    Eight,
+}
+
+impl Connectivity {
+    fn deltas(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &ORTHOGONAL,
+            Connectivity::Eight => &ALL_DIRECTIONS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from row-major cells.
+    ///
+    /// # Panics
+    /// If `cells` does not contain exactly `width * height` elements.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "cell count must match size");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Creates a grid by calling `f` for every position.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Pos) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(&mut f)
+            .collect();
+        Self::new(width, height, cells)
+    }
+
+    /// Parses a grid from text, mapping every character with `f`. Blank lines are skipped.
+    ///
+    /// # Panics
+    /// If the lines are not all of the same length.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Self {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for line in input.lines().map(str::trim_end) {
+            if line.is_empty() {
+                continue;
+            }
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+            let line_width = cells.len() - before;
+            assert_eq!(
+                *width.get_or_insert(line_width),
+                line_width,
+                "all grid rows must have the same width"
+            );
+            height += 1;
+        }
+
+        Self::new(width.unwrap_or(0), height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn in_bounds(&self, (row, col): Pos) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.in_bounds(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// Moves `pos` by `(dr, dc)`, returning `None` if the result is outside of the grid.
+    pub fn offset(&self, (row, col): Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        let pos = (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?);
+        self.in_bounds(pos).then_some(pos)
+    }
+
+    /// Iterates over all positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Iterates over all positions and their cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    /// Finds the first position in row-major order whose cell matches `predicate`.
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.cells
+            .iter()
+            .position(&mut predicate)
+            .map(|i| (i / self.width, i % self.width))
+    }
+
+    /// The in-bounds orthogonal neighbors of `pos`.
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, Connectivity::Four)
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbors of `pos`.
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.neighbors(pos, Connectivity::Eight)
+    }
+
+    pub fn neighbors(
+        &self,
+        pos: Pos,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = Pos> + '_ {
+        connectivity
+            .deltas()
+            .iter()
+            .filter_map(move |&delta| self.offset(pos, delta))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // NOTE: `chunks_exact` panics for a chunk size of 0.
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column {col} out of bounds");
+        self.cells.iter().skip(col).step_by(self.width.max(1))
+    }
+
+    /// Walks from `start` in direction `(dr, dc)` until leaving the grid, including `start`.
+    pub fn ray(&self, start: Pos, direction: (isize, isize)) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::successors(self.in_bounds(start).then_some(start), move |&pos| {
+            self.offset(pos, direction)
+        })
+    }
+
+    /// The cells on the diagonal from `start` towards the bottom-right.
+    pub fn diagonal(&self, start: Pos) -> impl Iterator<Item = &T> {
+        self.ray(start, (1, 1)).map(|pos| &self[pos])
+    }
+
+    /// The cells on the diagonal from `start` towards the bottom-left.
+    pub fn anti_diagonal(&self, start: Pos) -> impl Iterator<Item = &T> {
+        self.ray(start, (1, -1)).map(|pos| &self[pos])
+    }
+
+    /// Applies `f` to every cell, keeping the shape of the grid.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Collects the positions reachable from `start` via cells accepted by `can_enter`.
+    /// `start` itself is only included if it can be entered.
+    pub fn flood_fill(
+        &self,
+        start: Pos,
+        connectivity: Connectivity,
+        mut can_enter: impl FnMut(Pos, &T) -> bool,
+    ) -> Grid<bool> {
+        let mut visited = Grid::filled(self.width, self.height, false);
+
+        if !self.in_bounds(start) || !can_enter(start, &self[start]) {
+            return visited;
+        }
+
+        visited[start] = true;
+        let mut stack = vec![start];
+
+        while let Some(pos) = stack.pop() {
+            for next in self.neighbors(pos, connectivity) {
+                if !visited[next] && can_enter(next, &self[next]) {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Renders the grid as text with one line per row, mapping each cell to a character.
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+        let mut out = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            out.extend(row.iter().map(&mut f));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid with every cell set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(row, col)| {
+            self[(col, row)].clone()
+        })
+    }
+
+    /// Rotates the grid by 90 degrees clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(row, col)| {
+            self[(self.height - 1 - col, row)].clone()
+        })
+    }
+
+    /// Rotates the grid by 90 degrees counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |(row, col)| {
+            self[(col, self.width - 1 - row)].clone()
+        })
+    }
+
+    /// Mirrors the grid along its vertical axis.
+    pub fn flip_horizontal(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(row, col)| {
+            self[(row, self.width - 1 - col)].clone()
+        })
+    }
+
+    /// Mirrors the grid along its horizontal axis.
+    pub fn flip_vertical(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |(row, col)| {
+            self[(self.height - 1 - row, col)].clone()
+        })
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): Pos) -> &Self::Output {
+        assert!(col < self.width, "column {col} out of bounds");
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (row, col): Pos) -> &mut Self::Output {
+        assert!(col < self.width, "column {col} out of bounds");
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+impl Display for Grid<char> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(|&c| c))
+    }
+}
+
+impl Display for Grid<bool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(|&x| if x { '#' } else { '.' }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Connectivity, Grid};
+
+    fn sample() -> Grid<char> {
+        Grid::parse("abc\ndef\n", |c| c)
+    }
+
+    #[test]
+    fn parses_and_indexes() {
+        let grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], 'f');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.position(|&c| c == 'e'), Some((1, 1)));
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_ragged_rows() {
+        Grid::parse("abc\nde\n", |c| c);
+    }
+
+    #[test]
+    fn iterates_neighbors() {
+        let grid = Grid::filled(3, 3, 0);
+        assert_eq!(grid.neighbors4((0, 0)).count(), 2);
+        assert_eq!(grid.neighbors8((0, 0)).count(), 3);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbors4((2, 1)).count(), 3);
+    }
+
+    #[test]
+    fn walks_diagonals() {
+        let grid = Grid::parse("abc\ndef\nghi", |c| c);
+        assert_eq!(grid.diagonal((0, 0)).collect::<String>(), "aei");
+        assert_eq!(grid.anti_diagonal((0, 2)).collect::<String>(), "ceg");
+        assert_eq!(grid.diagonal((1, 2)).collect::<String>(), "f");
+    }
+
+    #[test]
+    fn rotates_and_transposes() {
+        let grid = sample();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc\n");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+    }
+
+    #[test]
+    fn flood_fills_regions() {
+        let grid = Grid::parse("..#\n.#.\n#..", |c| c == '#');
+        let four = grid.flood_fill((0, 0), Connectivity::Four, |_, &wall| !wall);
+        assert_eq!(four.to_string(), "##.\n#..\n...\n");
+
+        let eight = grid.flood_fill((0, 0), Connectivity::Eight, |_, &wall| !wall);
+        assert_eq!(eight.cells().iter().filter(|&&x| x).count(), 6);
+    }
+}
@@ -1,3 +1,17 @@
+pub mod compress;
+pub mod digits;
+pub mod dsu;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod linalg;
+pub mod memo;
+pub mod packing;
+pub mod parse;
+pub mod prefix_sum;
+pub mod ranges;
+pub mod spatial;
 pub mod template;
+pub mod viz;
 
 // Use this file to add helper functions and additional modules.
@@ -0,0 +1,562 @@
+//! Linear systems over the rationals and GF(2), and a small integer linear program minimiser.
+//!
+//! Systems are given as rows of integer coefficients `a` and a right-hand side `b`, i.e. `a · x = b`.
+
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// An exact fraction, always stored in lowest terms with a positive denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+fn lcm(a: i128, b: i128) -> i128 {
+    a / gcd(a, b) * b
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    /// # Panics
+    /// If `den` is zero.
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "denominator must not be zero");
+        let g = gcd(num, den);
+        let sign = den.signum();
+        Self {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn numer(self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(self) -> bool {
+        self.den == 1
+    }
+
+    /// The value as an integer, if it has no fractional part.
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self {
+            num: value.into(),
+            den: 1,
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// One row of a reduced system: `x[column] = rhs - Σ coefficients[i] * x[free[i]]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PivotRow {
+    pub column: usize,
+    pub rhs: Rational,
+    pub coefficients: Vec<Rational>,
+}
+
+/// The solution set of a consistent system over the rationals, parametrised by its free variables.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RationalSolution {
+    pub vars: usize,
+    pub pivots: Vec<PivotRow>,
+    pub free: Vec<usize>,
+}
+
+impl RationalSolution {
+    /// Whether the system has exactly one solution.
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// The full assignment for the given values of the free variables, in the order of `free`.
+    pub fn evaluate(&self, free_values: &[Rational]) -> Vec<Rational> {
+        assert_eq!(free_values.len(), self.free.len());
+
+        let mut x = vec![Rational::ZERO; self.vars];
+        for (&column, &value) in self.free.iter().zip(free_values) {
+            x[column] = value;
+        }
+        for row in &self.pivots {
+            x[row.column] = row
+                .coefficients
+                .iter()
+                .zip(free_values)
+                .fold(row.rhs, |acc, (&coef, &value)| acc - coef * value);
+        }
+        x
+    }
+
+    /// The solution with all free variables set to zero.
+    pub fn particular(&self) -> Vec<Rational> {
+        self.evaluate(&vec![Rational::ZERO; self.free.len()])
+    }
+}
+
+/// Solves `a · x = b` over the rationals with Gauss-Jordan elimination.
+/// Returns `None` if the system is inconsistent.
+pub fn solve_rational(a: &[Vec<i64>], b: &[i64]) -> Option<RationalSolution> {
+    assert_eq!(a.len(), b.len(), "every row needs a right-hand side");
+    let vars = a.first().map_or(0, Vec::len);
+
+    // Augmented matrix with the right-hand side as the last column.
+    let mut m: Vec<Vec<Rational>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &rhs)| {
+            assert_eq!(row.len(), vars, "all rows must have the same length");
+            row.iter().chain([&rhs]).map(|&x| x.into()).collect()
+        })
+        .collect();
+
+    let mut pivot_columns = Vec::new();
+    let mut rank = 0;
+
+    for column in 0..vars {
+        let Some(pivot) = (rank..m.len()).find(|&r| !m[r][column].is_zero()) else {
+            continue;
+        };
+        m.swap(rank, pivot);
+
+        let factor = m[rank][column];
+        for x in &mut m[rank] {
+            *x = *x / factor;
+        }
+
+        let pivot_row = m[rank].clone();
+        for (r, row) in m.iter_mut().enumerate() {
+            if r != rank && !row[column].is_zero() {
+                let factor = row[column];
+                for (x, &p) in row.iter_mut().zip(&pivot_row).skip(column) {
+                    *x = *x - factor * p;
+                }
+            }
+        }
+
+        pivot_columns.push(column);
+        rank += 1;
+    }
+
+    // A zero row with a non-zero right-hand side means `0 = c`.
+    if m[rank..].iter().any(|row| !row[vars].is_zero()) {
+        return None;
+    }
+
+    let free: Vec<usize> = (0..vars).filter(|c| !pivot_columns.contains(c)).collect();
+    let pivots = pivot_columns
+        .iter()
+        .zip(&m)
+        .map(|(&column, row)| PivotRow {
+            column,
+            rhs: row[vars],
+            coefficients: free.iter().map(|&f| row[f]).collect(),
+        })
+        .collect();
+
+    Some(RationalSolution { vars, pivots, free })
+}
+
+/// The solution set of a consistent system over GF(2): `particular` XOR any combination of `null_space`.
+/// Assignments are bitmasks with bit `i` holding `x[i]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gf2Solution {
+    pub particular: u64,
+    pub null_space: Vec<u64>,
+}
+
+impl Gf2Solution {
+    /// Iterates over all `2^k` solutions for a null space of dimension `k`.
+    pub fn solutions(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..1_u64 << self.null_space.len()).map(|combo| {
+            self.null_space
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| combo & (1 << i) != 0)
+                .fold(self.particular, |acc, (_, &v)| acc ^ v)
+        })
+    }
+
+    /// The solution with the fewest variables set.
+    pub fn min_weight(&self) -> u64 {
+        self.solutions()
+            .min_by_key(|x| x.count_ones())
+            .expect("there is at least one solution")
+    }
+}
+
+/// Solves a system over GF(2). Each equation is `(mask, rhs)`: the XOR of the variables in `mask` equals `rhs`.
+/// Returns `None` if the system is inconsistent.
+///
+/// # Panics
+/// If there are more than 64 variables.
+pub fn solve_gf2(equations: &[(u64, bool)], vars: usize) -> Option<Gf2Solution> {
+    assert!(vars <= 64, "at most 64 variables are supported");
+
+    let mut rows: Vec<(u64, bool)> = equations.to_vec();
+    let mut pivot_columns = Vec::new();
+    let mut rank = 0;
+
+    for column in 0..vars {
+        let bit = 1 << column;
+        let Some(pivot) = (rank..rows.len()).find(|&r| rows[r].0 & bit != 0) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+
+        let (mask, rhs) = rows[rank];
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != rank && row.0 & bit != 0 {
+                row.0 ^= mask;
+                row.1 ^= rhs;
+            }
+        }
+
+        pivot_columns.push(column);
+        rank += 1;
+    }
+
+    if rows[rank..].iter().any(|&(_, rhs)| rhs) {
+        return None;
+    }
+
+    let particular = pivot_columns
+        .iter()
+        .zip(&rows)
+        .filter(|&(_, &(_, rhs))| rhs)
+        .fold(0, |acc, (&column, _)| acc | (1 << column));
+
+    // Every free variable spans one basis vector: set it and solve the pivots for a zero right-hand side.
+    let null_space = (0..vars)
+        .filter(|c| !pivot_columns.contains(c))
+        .map(|free| {
+            pivot_columns
+                .iter()
+                .zip(&rows)
+                .filter(|&(_, &(mask, _))| mask & (1 << free) != 0)
+                .fold(1 << free, |acc, (&column, _)| acc | (1 << column))
+        })
+        .collect();
+
+    Some(Gf2Solution {
+        particular,
+        null_space,
+    })
+}
+
+/// A pivot row scaled to integers: `scale * x[column] = rhs - Σ coefficients[i] * x[free[i]]`.
+struct IntegerRow {
+    column: usize,
+    scale: i64,
+    rhs: i64,
+    coefficients: Vec<i64>,
+}
+
+impl IntegerRow {
+    fn new(row: &PivotRow) -> Self {
+        let scale = row
+            .coefficients
+            .iter()
+            .fold(row.rhs.denom(), |acc, x| lcm(acc, x.denom()));
+        let to_int = |x: Rational| i64::try_from(x.numer() * (scale / x.denom())).unwrap();
+
+        Self {
+            column: row.column,
+            scale: scale.try_into().unwrap(),
+            rhs: to_int(row.rhs),
+            coefficients: row.coefficients.iter().map(|&x| to_int(x)).collect(),
+        }
+    }
+}
+
+/// Minimises `cost · x` subject to `a · x = b` and `0 <= x[i] <= upper[i]` for integer `x`.
+/// Returns the optimal cost and assignment, or `None` if no integer solution exists.
+///
+/// The system is reduced to its free variables first, which are then assigned by a depth-first
+/// branch and bound that prunes on the feasible range of every pivot variable and on the cost.
+pub fn minimize_ilp(
+    cost: &[i64],
+    a: &[Vec<i64>],
+    b: &[i64],
+    upper: &[i64],
+) -> Option<(i64, Vec<i64>)> {
+    let solution = solve_rational(a, b)?;
+    assert_eq!(cost.len(), solution.vars);
+    assert_eq!(upper.len(), solution.vars);
+
+    let rows: Vec<IntegerRow> = solution.pivots.iter().map(IntegerRow::new).collect();
+    let free = &solution.free;
+
+    // The cost as a function of the free variables: `base + Σ slope[i] * x[free[i]]`.
+    let base: f64 = solution
+        .pivots
+        .iter()
+        .map(|row| cost[row.column] as f64 * row.rhs.to_f64())
+        .sum();
+    let slope: Vec<f64> = (0..free.len())
+        .map(|i| {
+            cost[free[i]] as f64
+                - solution
+                    .pivots
+                    .iter()
+                    .map(|row| cost[row.column] as f64 * row.coefficients[i].to_f64())
+                    .sum::<f64>()
+        })
+        .collect();
+
+    // rest[r][i]: the range of `-Σ coefficients[j] * x[free[j]]` over the free variables `j >= i`.
+    let rest: Vec<Vec<(i64, i64)>> = rows
+        .iter()
+        .map(|row| {
+            let mut ranges = vec![(0, 0); free.len() + 1];
+            for i in (0..free.len()).rev() {
+                let term = -row.coefficients[i] * upper[free[i]];
+                let (lo, hi) = ranges[i + 1];
+                ranges[i] = (lo + term.min(0), hi + term.max(0));
+            }
+            ranges
+        })
+        .collect();
+    let cost_rest: Vec<f64> = {
+        let mut ranges = vec![0.0; free.len() + 1];
+        for i in (0..free.len()).rev() {
+            ranges[i] = ranges[i + 1] + (slope[i] * upper[free[i]] as f64).min(0.0);
+        }
+        ranges
+    };
+
+    struct Search<'a> {
+        cost: &'a [i64],
+        upper: &'a [i64],
+        free: &'a [usize],
+        rows: &'a [IntegerRow],
+        slope: &'a [f64],
+        rest: &'a [Vec<(i64, i64)>],
+        cost_rest: &'a [f64],
+        x: Vec<i64>,
+        best: Option<(i64, Vec<i64>)>,
+    }
+
+    impl Search<'_> {
+        fn run(&mut self, depth: usize, partial: &mut [i64], partial_cost: f64) {
+            // Every pivot must still be able to land in `0..=upper`.
+            for ((row, ranges), &value) in self.rows.iter().zip(self.rest).zip(partial.iter()) {
+                let (lo, hi) = ranges[depth];
+                if value + hi < 0 || value + lo > row.scale * self.upper[row.column] {
+                    return;
+                }
+            }
+            if let Some((best, _)) = &self.best
+                && partial_cost + self.cost_rest[depth] >= *best as f64 - 1e-9
+            {
+                return;
+            }
+
+            if depth == self.free.len() {
+                for (row, &value) in self.rows.iter().zip(partial.iter()) {
+                    if value % row.scale != 0 {
+                        return;
+                    }
+                    self.x[row.column] = value / row.scale;
+                }
+                let total: i64 = self.cost.iter().zip(&self.x).map(|(c, x)| c * x).sum();
+                if self.best.as_ref().is_none_or(|(best, _)| total < *best) {
+                    self.best = Some((total, self.x.clone()));
+                }
+                return;
+            }
+
+            let column = self.free[depth];
+            for value in 0..=self.upper[column] {
+                self.x[column] = value;
+                for (row, p) in self.rows.iter().zip(partial.iter_mut()) {
+                    *p -= row.coefficients[depth] * value;
+                }
+                self.run(
+                    depth + 1,
+                    partial,
+                    partial_cost + self.slope[depth] * value as f64,
+                );
+                for (row, p) in self.rows.iter().zip(partial.iter_mut()) {
+                    *p += row.coefficients[depth] * value;
+                }
+            }
+        }
+    }
+
+    let mut search = Search {
+        cost,
+        upper,
+        free,
+        rows: &rows,
+        slope: &slope,
+        rest: &rest,
+        cost_rest: &cost_rest,
+        x: vec![0; solution.vars],
+        best: None,
+    };
+    let mut partial: Vec<i64> = rows.iter().map(|row| row.rhs).collect();
+    search.run(0, &mut partial, base);
+    search.best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rational, minimize_ilp, solve_gf2, solve_rational};
+
+    #[test]
+    fn normalizes_rationals() {
+        assert_eq!(Rational::new(2, -4), Rational::new(-1, 2));
+        assert_eq!(
+            Rational::new(1, 3) + Rational::new(1, 6),
+            Rational::new(1, 2)
+        );
+        assert_eq!(
+            Rational::new(3, 4) / Rational::new(3, 2),
+            Rational::new(1, 2)
+        );
+        assert_eq!(Rational::new(6, 3).to_integer(), Some(2));
+        assert_eq!(Rational::new(-5, 10).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn solves_unique_rational_systems() {
+        // x + y = 3, x - y = 1
+        let solution = solve_rational(&[vec![1, 1], vec![1, -1]], &[3, 1]).unwrap();
+        assert!(solution.is_unique());
+        assert_eq!(
+            solution.particular(),
+            vec![Rational::from(2), Rational::from(1)]
+        );
+
+        // 2x = 1
+        let solution = solve_rational(&[vec![2]], &[1]).unwrap();
+        assert_eq!(solution.particular(), vec![Rational::new(1, 2)]);
+    }
+
+    #[test]
+    fn parametrises_free_variables() {
+        // x + y + z = 6, y - z = 1
+        let solution = solve_rational(&[vec![1, 1, 1], vec![0, 1, -1]], &[6, 1]).unwrap();
+        assert_eq!(solution.free, vec![2]);
+        let x = solution.evaluate(&[Rational::from(2)]);
+        assert_eq!(
+            x,
+            vec![Rational::from(1), Rational::from(3), Rational::from(2)]
+        );
+    }
+
+    #[test]
+    fn detects_inconsistent_systems() {
+        assert!(solve_rational(&[vec![1, 1], vec![2, 2]], &[1, 3]).is_none());
+        assert!(solve_gf2(&[(0b11, true), (0b11, false)], 2).is_none());
+    }
+
+    #[test]
+    fn solves_gf2_systems() {
+        // x0 ^ x1 = 1, x1 ^ x2 = 0
+        let solution = solve_gf2(&[(0b011, true), (0b110, false)], 3).unwrap();
+        assert_eq!(solution.null_space.len(), 1);
+        for x in solution.solutions() {
+            assert_eq!((x & 0b011).count_ones() % 2, 1);
+            assert_eq!((x & 0b110).count_ones() % 2, 0);
+        }
+        assert_eq!(solution.min_weight(), 0b001);
+    }
+
+    #[test]
+    fn minimizes_integer_programs() {
+        // Minimise x + y + z with x + y = 4, y + z = 5 and everything in 0..=5.
+        let (cost, x) = minimize_ilp(
+            &[1, 1, 1],
+            &[vec![1, 1, 0], vec![0, 1, 1]],
+            &[4, 5],
+            &[5, 5, 5],
+        )
+        .unwrap();
+        assert_eq!(cost, 5);
+        assert_eq!(x, vec![0, 4, 1]);
+
+        // 2x = 3 has no integer solution.
+        assert_eq!(minimize_ilp(&[1], &[vec![2]], &[3], &[5]), None);
+    }
+}
@@ -4,10 +4,18 @@ use args::{AppArguments, parse};
 #[cfg(feature = "today")]
 use advent_of_code::template::Day;
 #[cfg(feature = "today")]
+use advent_of_code::template::commands::scaffold::ScaffoldOptions;
+#[cfg(feature = "today")]
 use std::process;
 
 mod args {
-    use advent_of_code::template::Day;
+    use advent_of_code::template::commands::scaffold::ScaffoldOptions;
+    use advent_of_code::template::commands::solve::SolveOptions;
+    use advent_of_code::template::runner::{BenchOptions, VizOptions};
+    use advent_of_code::template::{
+        Day,
+        solution_args::{parse_part, split_day_args},
+    };
     use std::process;
 
     pub enum AppArguments {
@@ -20,41 +28,51 @@ mod args {
         Scaffold {
             day: Day,
             download: bool,
-            overwrite: bool,
+            options: ScaffoldOptions,
         },
         Solve {
             day: Day,
-            release: bool,
-            dhat: bool,
-            submit: Option<u8>,
+            options: SolveOptions,
         },
         All {
             release: bool,
+            part: Option<u8>,
         },
         Time {
             all: bool,
             day: Option<Day>,
             store: bool,
+            perf: bool,
+            bench: BenchOptions,
+            part: Option<u8>,
         },
         #[cfg(feature = "today")]
         Today,
     }
 
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
-        let mut args = pico_args::Arguments::from_env();
+        let (args, mut day_args) = split_day_args(std::env::args_os().skip(1).collect());
+        let mut args = pico_args::Arguments::from_vec(args);
 
         let app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
+                part: args.opt_value_from_fn("--part", parse_part)?,
             },
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let perf = args.contains("--perf");
+                let bench = BenchOptions::from_args(&mut args)?;
+                let part = args.opt_value_from_fn("--part", parse_part)?;
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    perf,
+                    bench,
+                    part,
                 }
             }
             Some("download") => AppArguments::Download {
@@ -63,17 +81,43 @@ mod args {
             Some("read") => AppArguments::Read {
                 day: args.free_from_str()?,
             },
-            Some("scaffold") => AppArguments::Scaffold {
-                day: args.free_from_str()?,
-                download: args.contains("--download"),
-                overwrite: args.contains("--overwrite"),
-            },
-            Some("solve") => AppArguments::Solve {
-                day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                dhat: args.contains("--dhat"),
-            },
+            Some("scaffold") => {
+                let options = ScaffoldOptions {
+                    overwrite: args.contains("--overwrite"),
+                    template: args.opt_value_from_str("--template")?,
+                    dry_run: args.contains("--dry-run"),
+                    answers: args
+                        .opt_value_from_fn("--answers", parse_answers)?
+                        .unwrap_or_default(),
+                };
+
+                AppArguments::Scaffold {
+                    day: args.free_from_str()?,
+                    download: args.contains("--download"),
+                    options,
+                }
+            }
+            Some("solve") => {
+                let time = args.contains("--time");
+                let bench = BenchOptions::from_args(&mut args)?;
+                let part = args.opt_value_from_fn("--part", parse_part)?;
+                let viz = VizOptions::from_args(&mut args)?;
+
+                AppArguments::Solve {
+                    day: args.free_from_str()?,
+                    options: SolveOptions {
+                        release: args.contains("--release"),
+                        submit: args.opt_value_from_str("--submit")?,
+                        dhat: args.contains("--dhat"),
+                        perf: args.contains("--perf"),
+                        // passing any bench option implies `--time`.
+                        bench: (time || bench != BenchOptions::default()).then_some(bench),
+                        part,
+                        viz,
+                        day_args: std::mem::take(&mut day_args),
+                    },
+                }
+            }
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
             Some(x) => {
@@ -90,9 +134,31 @@ mod args {
         if !remaining.is_empty() {
             eprintln!("Warning: unknown argument(s): {remaining:?}.");
         }
+        if !day_args.as_slice().is_empty() {
+            eprintln!(
+                "Warning: day options are only used by `solve`: {:?}.",
+                day_args.as_slice()
+            );
+        }
 
         Ok(app_args)
     }
+
+    /// Parse example answers like `142` or `142,281` for part one and two.
+    fn parse_answers(s: &str) -> Result<[Option<String>; 2], String> {
+        let mut answers = s.split(',').map(|x| {
+            let x = x.trim();
+            (!x.is_empty()).then(|| x.to_string())
+        });
+
+        let parsed = [answers.next().flatten(), answers.next().flatten()];
+
+        if answers.next().is_some() {
+            return Err(format!("expected at most two answers, got `{s}`"));
+        }
+
+        Ok(parsed)
+    }
 }
 
 fn main() {
@@ -102,31 +168,33 @@ fn main() {
             std::process::exit(1);
         }
         Ok(args) => match args {
-            AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::All { release, part } => all::handle(release, part),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                perf,
+                bench,
+                part,
+            } => time::handle(day, all, store, perf, &bench, part),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold {
                 day,
                 download,
-                overwrite,
+                options,
             } => {
-                scaffold::handle(day, overwrite);
-                if download {
+                scaffold::handle(day, &options);
+                if download && !options.dry_run {
                     download::handle(day);
                 }
             }
-            AppArguments::Solve {
-                day,
-                release,
-                dhat,
-                submit,
-            } => solve::handle(day, release, dhat, submit),
+            AppArguments::Solve { day, options } => solve::handle(day, &options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
                     Some(day) => {
-                        scaffold::handle(day, false);
+                        scaffold::handle(day, &ScaffoldOptions::default());
                         download::handle(day);
                         read::handle(day)
                     }
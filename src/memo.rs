@@ -0,0 +1,341 @@
+//! Memoisation for recursive solvers, with hash or dense array storage and hit/miss statistics.
+//!
+//! Two evaluation styles are supported:
+//! - [`Memo::get_or_compute`] for plain recursion, where the computation may call back into the memo;
+//! - [`Memo::evaluate`] for deep recursion, which walks the dependencies with an explicit stack.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Storage for memoised values.
+pub trait Store<K, V> {
+    fn get(&self, key: &K) -> Option<&V>;
+    fn insert(&mut self, key: K, value: V);
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq, V> Store<K, V> for HashMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        HashMap::insert(self, key, value);
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self);
+    }
+}
+
+/// Keys that map to a small index, for [`DenseStore`].
+pub trait DenseKey {
+    fn index(&self) -> usize;
+}
+
+macro_rules! impl_dense_key {
+    ($($t:ty),*) => {
+        $(
+            impl DenseKey for $t {
+                fn index(&self) -> usize {
+                    *self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_dense_key!(u8, u16, u32, u64, usize);
+
+/// A fixed-size array of slots, indexed by [`DenseKey::index`].
+#[derive(Clone, Debug)]
+pub struct DenseStore<V> {
+    slots: Vec<Option<V>>,
+    len: usize,
+}
+
+impl<V> DenseStore<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: std::iter::repeat_with(|| None).take(capacity).collect(),
+            len: 0,
+        }
+    }
+}
+
+impl<K: DenseKey, V> Store<K, V> for DenseStore<V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        self.slots.get(key.index())?.as_ref()
+    }
+
+    /// # Panics
+    /// If the key's index is not below the capacity.
+    fn insert(&mut self, key: K, value: V) {
+        let (index, capacity) = (key.index(), self.slots.len());
+        let slot = self.slots.get_mut(index).unwrap_or_else(|| {
+            panic!("key index {index} out of range for a dense memo of capacity {capacity}")
+        });
+        if slot.replace(value).is_none() {
+            self.len += 1;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+}
+
+/// How often a memo answered from storage and how often it had to compute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Stats {
+    /// The fraction of lookups answered from storage, or `0` without lookups.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+/// Memoised values of a function from `K` to `V`.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V, S = HashMap<K, V>> {
+    store: S,
+    stats: Stats,
+    _marker: PhantomData<fn(K) -> V>,
+}
+
+impl<K: Hash + Eq, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::with_store(HashMap::new())
+    }
+}
+
+impl<K: Hash + Eq, V> Memo<K, V> {
+    /// A memo backed by a `HashMap`, for arbitrary hashable state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: DenseKey, V> Memo<K, V, DenseStore<V>> {
+    /// A memo backed by an array, for keys whose index is below `capacity`.
+    pub fn dense(capacity: usize) -> Self {
+        Self::with_store(DenseStore::new(capacity))
+    }
+}
+
+impl<K, V, S: Store<K, V>> Memo<K, V, S> {
+    pub fn with_store(store: S) -> Self {
+        Self {
+            store,
+            stats: Stats::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The number of memoised values.
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Forgets all values and resets the statistics.
+    pub fn clear(&mut self) {
+        self.store.clear();
+        self.stats = Stats::default();
+    }
+
+    /// The memoised value for `key`, without counting a lookup.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.store.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.store.insert(key, value);
+    }
+}
+
+impl<K, V: Clone, S: Store<K, V>> Memo<K, V, S> {
+    /// The value for `key`, computed by `compute` unless it is memoised already.
+    /// `compute` receives the memo, so it can look up the values it depends on recursively.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.store.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.store.insert(key, value.clone());
+        value
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: Store<K, V>> Memo<K, V, S> {
+    /// The value for `key` without recursion: `dependencies` lists the keys a key depends on, and
+    /// `combine` computes its value from theirs, in the same order. Suitable for arbitrarily deep
+    /// dependency chains.
+    ///
+    /// # Panics
+    /// If the dependencies contain a cycle.
+    pub fn evaluate<I: IntoIterator<Item = K>>(
+        &mut self,
+        key: K,
+        mut dependencies: impl FnMut(&K) -> I,
+        mut combine: impl FnMut(&K, &[V]) -> V,
+    ) -> V {
+        // Keys whose dependencies are being evaluated, i.e. the expanded frames of the stack.
+        let mut in_progress = HashSet::new();
+        // (key, its dependencies once expanded)
+        let mut stack: Vec<(K, Option<Vec<K>>)> = vec![(key.clone(), None)];
+
+        while let Some((current, expanded)) = stack.pop() {
+            match expanded {
+                None => {
+                    if self.store.get(&current).is_some() {
+                        self.stats.hits += 1;
+                        continue;
+                    }
+                    let deps: Vec<K> = dependencies(&current).into_iter().collect();
+                    let missing: Vec<K> = deps
+                        .iter()
+                        .filter(|&dep| self.store.get(dep).is_none())
+                        .cloned()
+                        .collect();
+                    self.stats.hits += (deps.len() - missing.len()) as u64;
+
+                    in_progress.insert(current.clone());
+                    stack.push((current, Some(deps)));
+                    for dep in missing {
+                        assert!(
+                            !in_progress.contains(&dep),
+                            "memoised dependencies contain a cycle"
+                        );
+                        stack.push((dep, None));
+                    }
+                }
+                Some(deps) => {
+                    let values: Vec<V> = deps
+                        .iter()
+                        .map(|dep| self.store.get(dep).unwrap().clone())
+                        .collect();
+                    self.stats.misses += 1;
+                    let value = combine(&current, &values);
+                    in_progress.remove(&current);
+                    self.store.insert(current, value);
+                }
+            }
+        }
+
+        self.store.get(&key).unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Memo, Stats};
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+    }
+
+    #[test]
+    fn memoises_recursive_calls() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(&mut memo, 90), 2_880_067_194_370_816_120);
+        assert_eq!(memo.len(), 89);
+        assert_eq!(
+            memo.stats(),
+            Stats {
+                hits: 87,
+                misses: 89
+            }
+        );
+
+        fibonacci(&mut memo, 90);
+        assert_eq!(memo.stats().hits, 88);
+        assert!(memo.stats().hit_rate() > 0.49);
+    }
+
+    #[test]
+    fn supports_arbitrary_state() {
+        // Lattice paths to the origin, keyed by position.
+        fn paths(memo: &mut Memo<(u32, u32), u64>, (x, y): (u32, u32)) -> u64 {
+            if x == 0 || y == 0 {
+                return 1;
+            }
+            memo.get_or_compute((x, y), |memo| {
+                paths(memo, (x - 1, y)) + paths(memo, (x, y - 1))
+            })
+        }
+
+        let mut memo = Memo::new();
+        assert_eq!(paths(&mut memo, (16, 16)), 601_080_390);
+        assert_eq!(memo.len(), 256);
+    }
+
+    #[test]
+    fn uses_dense_storage() {
+        let mut memo = Memo::<usize, u64, _>::dense(10);
+        let value = memo.evaluate(
+            9,
+            |&n| (1..=n).map(move |k| n - k),
+            |_, values| values.iter().sum::<u64>().max(1),
+        );
+        assert_eq!(value, 256);
+        assert_eq!(memo.len(), 10);
+        assert_eq!(memo.get(&4), Some(&8));
+    }
+
+    #[test]
+    fn evaluates_deep_chains_iteratively() {
+        let mut memo = Memo::dense(1_000_001);
+        let deps = |&n: &usize| (n > 0).then(|| n - 1);
+        let value = memo.evaluate(1_000_000, deps, |_, values: &[u64]| {
+            values.first().map_or(0, |v| v + 1)
+        });
+        assert_eq!(value, 1_000_000);
+        assert_eq!(memo.stats().misses, 1_000_001);
+
+        // A second evaluation is answered from storage.
+        memo.evaluate(500, deps, |_, _| unreachable!());
+        assert_eq!(memo.stats().hits, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn detects_cycles() {
+        let mut memo: Memo<u8, u8> = Memo::new();
+        memo.evaluate(0, |&n| [(n + 1) % 3], |_, values| values[0]);
+    }
+}
@@ -0,0 +1,567 @@
+//! Polyomino packing as an exact cover problem, solved with the `dlx` crate.
+//!
+//! Every piece type becomes a primary item that has to be covered exactly `quantity` times,
+//! so identical copies of a piece are not tried in every permutation. Cells of the region are
+//! secondary items (at most one piece per cell) or, for exact fills, primary items.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use dlx::choose::{mrv_chooser, no_tiebreak, prefer_any};
+use dlx::{Int, OptOrder, Solver, Uint};
+
+/// A `(row, col)` offset of a polyomino cell.
+pub type Cell = (i32, i32);
+
+/// A set of cells, normalized so that its minimum row and column are `0`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Polyomino {
+    cells: Vec<Cell>,
+}
+
+impl Polyomino {
+    pub fn new(cells: impl IntoIterator<Item = Cell>) -> Self {
+        let mut cells: Vec<Cell> = cells.into_iter().collect();
+        let min_row = cells.iter().map(|&(r, _)| r).min().unwrap_or(0);
+        let min_col = cells.iter().map(|&(_, c)| c).min().unwrap_or(0);
+        for (r, c) in &mut cells {
+            *r -= min_row;
+            *c -= min_col;
+        }
+        cells.sort_unstable();
+        cells.dedup();
+        Self { cells }
+    }
+
+    /// Parses a shape drawn with `#` for filled and any other character for empty cells.
+    pub fn parse(drawing: &str) -> Self {
+        Self::new(drawing.lines().enumerate().flat_map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|&(_, ch)| ch == '#')
+                .map(move |(col, _)| (row as i32, col as i32))
+        }))
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// The number of cells.
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|&(_, c)| c + 1).max().unwrap_or(0) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.iter().map(|&(r, _)| r + 1).max().unwrap_or(0) as usize
+    }
+
+    /// Rotates the shape by 90 degrees clockwise.
+    pub fn rotate(&self) -> Self {
+        Self::new(self.cells.iter().map(|&(r, c)| (c, -r)))
+    }
+
+    /// Mirrors the shape horizontally.
+    pub fn flip(&self) -> Self {
+        Self::new(self.cells.iter().map(|&(r, c)| (r, -c)))
+    }
+
+    /// The distinct orientations of the shape that are allowed by `symmetry`.
+    pub fn orientations(&self, symmetry: Symmetry) -> Vec<Polyomino> {
+        let mut seen = HashSet::new();
+        let mut orientations = Vec::new();
+
+        let bases = match symmetry {
+            Symmetry::Fixed => return vec![self.clone()],
+            Symmetry::Rotations => vec![self.clone()],
+            Symmetry::RotationsAndFlips => vec![self.clone(), self.flip()],
+        };
+
+        for mut current in bases {
+            for _ in 0..4 {
+                if seen.insert(current.clone()) {
+                    orientations.push(current.clone());
+                }
+                current = current.rotate();
+            }
+        }
+
+        orientations
+    }
+}
+
+/// Which transformations may be applied to pieces when placing them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Pieces are only translated.
+    Fixed,
+    Rotations,
+    #[default]
+    RotationsAndFlips,
+}
+
+/// Whether every free cell of the region has to be covered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fill {
+    /// Cells may stay empty.
+    #[default]
+    Partial,
+    /// Every cell that is not blocked has to be covered by a piece.
+    Exact,
+}
+
+/// One piece put into the region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// The index of the piece in the order it was added with [`Packing::piece`].
+    pub piece: usize,
+    /// The `(row, col)` cells covered by the piece.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// A set of placements that satisfies a [`Packing`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub width: usize,
+    pub height: usize,
+    pub placements: Vec<Placement>,
+}
+
+impl Solution {
+    /// Draws the region with one letter per placed piece and `.` for empty cells.
+    pub fn render(&self) -> String {
+        const LABELS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+        let mut rows = vec![vec!['.'; self.width]; self.height];
+        for (i, placement) in self.placements.iter().enumerate() {
+            let label = LABELS.get(i).map_or('#', |&x| x as char);
+            for &(row, col) in &placement.cells {
+                rows[row][col] = label;
+            }
+        }
+
+        rows.into_iter()
+            .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+            .collect()
+    }
+}
+
+/// A packing problem: pieces with quantities to be placed without overlap into a rectangular region.
+#[derive(Clone, Debug)]
+pub struct Packing {
+    width: usize,
+    height: usize,
+    pieces: Vec<(Polyomino, usize)>,
+    blocked: HashSet<(usize, usize)>,
+    symmetry: Symmetry,
+    fill: Fill,
+}
+
+impl Packing {
+    /// Creates an empty `width` x `height` region.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pieces: Vec::new(),
+            blocked: HashSet::new(),
+            symmetry: Symmetry::default(),
+            fill: Fill::default(),
+        }
+    }
+
+    /// Adds `quantity` copies of `shape`.
+    pub fn piece(mut self, shape: Polyomino, quantity: usize) -> Self {
+        self.pieces.push((shape, quantity));
+        self
+    }
+
+    /// Marks `(row, col)` cells that no piece may cover.
+    pub fn blocked(mut self, cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        self.blocked.extend(cells);
+        self
+    }
+
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// The number of cells pieces can be placed on.
+    pub fn free_area(&self) -> usize {
+        let blocked = self
+            .blocked
+            .iter()
+            .filter(|&&(r, c)| r < self.height && c < self.width)
+            .count();
+        self.width * self.height - blocked
+    }
+
+    /// The number of cells covered once all pieces are placed.
+    pub fn piece_area(&self) -> usize {
+        self.pieces
+            .iter()
+            .map(|(shape, quantity)| shape.area() * quantity)
+            .sum()
+    }
+
+    /// All ways to put a single piece into the region, deduplicated across symmetric orientations.
+    pub fn placements(&self) -> Vec<Placement> {
+        let mut placements = Vec::new();
+
+        for (piece, (shape, quantity)) in self.pieces.iter().enumerate() {
+            if *quantity == 0 {
+                continue;
+            }
+            for orientation in shape.orientations(self.symmetry) {
+                let (w, h) = (orientation.width(), orientation.height());
+                if w > self.width || h > self.height {
+                    continue;
+                }
+                for row in 0..=self.height - h {
+                    for col in 0..=self.width - w {
+                        let cells: Vec<(usize, usize)> = orientation
+                            .cells()
+                            .iter()
+                            .map(|&(r, c)| (row + r as usize, col + c as usize))
+                            .collect();
+                        if cells.iter().all(|cell| !self.blocked.contains(cell)) {
+                            placements.push(Placement { piece, cells });
+                        }
+                    }
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Calls `f` for every solution until it returns [`ControlFlow::Break`].
+    /// Returns the number of solutions visited.
+    pub fn for_each_solution(&self, mut f: impl FnMut(&Solution) -> ControlFlow<()>) -> usize {
+        let area_fits = match self.fill {
+            Fill::Partial => self.piece_area() <= self.free_area(),
+            Fill::Exact => self.piece_area() == self.free_area(),
+        };
+        if !area_fits {
+            return 0;
+        }
+
+        let pieces: Vec<usize> = (0..self.pieces.len())
+            .filter(|&i| self.pieces[i].1 > 0)
+            .collect();
+
+        // Nothing to place: the empty placement is the only solution.
+        if pieces.is_empty() {
+            let _ = f(&self.solution(&[], &[]));
+            return 1;
+        }
+
+        // Items: one per piece type with its quantity as multiplicity, then one per free cell.
+        let mut cell_items = vec![None; self.width * self.height];
+        let mut free_cells = 0;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if !self.blocked.contains(&(row, col)) {
+                    cell_items[row * self.width + col] = Some(pieces.len() + free_cells);
+                    free_cells += 1;
+                }
+            }
+        }
+
+        let placements = self.placements();
+        let options: Vec<Vec<Uint>> = placements
+            .iter()
+            .map(|placement| {
+                let item = pieces.iter().position(|&x| x == placement.piece).unwrap();
+                let cells = placement.cells.iter().map(|&(row, col)| {
+                    cell_items[row * self.width + col].expect("placements avoid blocked cells")
+                });
+                std::iter::once(item)
+                    .chain(cells)
+                    .map(|x| x as Uint)
+                    .collect()
+            })
+            .collect();
+
+        if options.is_empty() {
+            return 0;
+        }
+
+        let mut bounds: Vec<(Int, Int)> = pieces
+            .iter()
+            .map(|&i| (self.pieces[i].1 as Int, self.pieces[i].1 as Int))
+            .collect();
+        let secondary = match self.fill {
+            Fill::Partial => free_cells,
+            Fill::Exact => {
+                bounds.extend((0..free_cells).map(|_| (1, 1)));
+                0
+            }
+        };
+
+        let items = dlx::m::INodes::new(bounds.iter().copied(), secondary as Uint);
+        let opts = dlx::x::ONodes::new(
+            (pieces.len() + free_cells) as Uint,
+            options.len() as Uint,
+            &options,
+            OptOrder::Seq,
+        );
+        let mut problem = dlx::m::Problem::new(items, opts);
+        let mut solver = Solver::new(&mut problem);
+        let mut chooser = mrv_chooser(prefer_any(), no_tiebreak());
+
+        let mut count = 0;
+        while solver.next_solution(&mut chooser) {
+            count += 1;
+            let chosen: Vec<usize> = solver.fmt_solution().iter().map(|&x| x as usize).collect();
+            if f(&self.solution(&placements, &chosen)).is_break() {
+                break;
+            }
+        }
+        count
+    }
+
+    fn solution(&self, placements: &[Placement], chosen: &[usize]) -> Solution {
+        Solution {
+            width: self.width,
+            height: self.height,
+            placements: chosen.iter().map(|&i| placements[i].clone()).collect(),
+        }
+    }
+
+    /// Whether all pieces can be placed.
+    pub fn is_solvable(&self) -> bool {
+        self.first_solution().is_some()
+    }
+
+    pub fn first_solution(&self) -> Option<Solution> {
+        let mut first = None;
+        self.for_each_solution(|solution| {
+            first = Some(solution.clone());
+            ControlFlow::Break(())
+        });
+        first
+    }
+
+    /// Counts the solutions, stopping early once `limit` solutions were found.
+    /// Solutions that only differ in which copy of a piece is used are counted once.
+    pub fn count_solutions(&self, limit: Option<usize>) -> usize {
+        let mut count = 0;
+        self.for_each_solution(|_| {
+            count += 1;
+            if limit.is_some_and(|limit| count >= limit) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Checks that `solution` satisfies the packing: every piece is placed as often as required
+    /// in an allowed orientation, inside the region, off blocked cells and without overlaps.
+    /// Useful for solutions that were built by hand rather than by the solver.
+    pub fn check(&self, solution: &Solution) -> Result<(), String> {
+        if (solution.width, solution.height) != (self.width, self.height) {
+            return Err(format!(
+                "solution is {}x{}, expected {}x{}",
+                solution.width, solution.height, self.width, self.height
+            ));
+        }
+
+        let orientations: Vec<Vec<Polyomino>> = self
+            .pieces
+            .iter()
+            .map(|(shape, _)| shape.orientations(self.symmetry))
+            .collect();
+        let mut counts = vec![0; self.pieces.len()];
+        let mut covered = vec![false; self.width * self.height];
+        let mut covered_count = 0;
+        for (i, placement) in solution.placements.iter().enumerate() {
+            let Some(allowed) = orientations.get(placement.piece) else {
+                return Err(format!(
+                    "placement {i} uses unknown piece {}",
+                    placement.piece
+                ));
+            };
+            counts[placement.piece] += 1;
+
+            let placed = Polyomino::new(placement.cells.iter().map(|&(r, c)| (r as i32, c as i32)));
+            if placed.area() != placement.cells.len() || !allowed.contains(&placed) {
+                return Err(format!(
+                    "placement {i} is not an orientation of piece {}",
+                    placement.piece
+                ));
+            }
+
+            for &cell in &placement.cells {
+                if cell.0 >= self.height || cell.1 >= self.width {
+                    return Err(format!("placement {i} leaves the region at {cell:?}"));
+                }
+                if self.blocked.contains(&cell) {
+                    return Err(format!("placement {i} covers blocked cell {cell:?}"));
+                }
+                if std::mem::replace(&mut covered[cell.0 * self.width + cell.1], true) {
+                    return Err(format!("placement {i} overlaps another at {cell:?}"));
+                }
+                covered_count += 1;
+            }
+        }
+
+        for (piece, (&count, (_, quantity))) in counts.iter().zip(&self.pieces).enumerate() {
+            if count != *quantity {
+                return Err(format!(
+                    "piece {piece} is placed {count} times, expected {quantity}"
+                ));
+            }
+        }
+
+        if self.fill == Fill::Exact && covered_count != self.free_area() {
+            return Err(format!(
+                "{} of {} free cells are covered",
+                covered_count,
+                self.free_area()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fill, Packing, Placement, Polyomino, Solution, Symmetry};
+
+    fn l_tromino() -> Polyomino {
+        Polyomino::parse("#.\n##")
+    }
+
+    #[test]
+    fn deduplicates_orientations() {
+        let square = Polyomino::parse("##\n##");
+        assert_eq!(square.orientations(Symmetry::RotationsAndFlips).len(), 1);
+        assert_eq!(
+            l_tromino().orientations(Symmetry::RotationsAndFlips).len(),
+            4
+        );
+        assert_eq!(l_tromino().orientations(Symmetry::Fixed).len(), 1);
+
+        let l_tetromino = Polyomino::parse("#.\n#.\n##");
+        assert_eq!(l_tetromino.orientations(Symmetry::Rotations).len(), 4);
+        assert_eq!(
+            l_tetromino.orientations(Symmetry::RotationsAndFlips).len(),
+            8
+        );
+    }
+
+    #[test]
+    fn generates_placements() {
+        let packing = Packing::new(3, 2).piece(l_tromino(), 1);
+        // 4 orientations with 2 positions each.
+        assert_eq!(packing.placements().len(), 8);
+
+        let blocked = Packing::new(3, 2).piece(l_tromino(), 1).blocked([(0, 0)]);
+        assert_eq!(blocked.placements().len(), 5);
+    }
+
+    #[test]
+    fn solves_partial_fills() {
+        let packing = Packing::new(3, 3).piece(l_tromino(), 2);
+        let solution = packing.first_solution().unwrap();
+        assert_eq!(solution.placements.len(), 2);
+        assert!(
+            Packing::new(3, 3)
+                .piece(Polyomino::parse("##\n##"), 2)
+                .first_solution()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn counts_exact_covers() {
+        // Domino tilings of a 2x3 rectangle.
+        let domino = Polyomino::parse("##");
+        let packing = Packing::new(3, 2)
+            .piece(domino.clone(), 3)
+            .fill(Fill::Exact);
+        assert_eq!(packing.count_solutions(None), 3);
+        assert_eq!(packing.count_solutions(Some(2)), 2);
+
+        let blocked = Packing::new(3, 2)
+            .piece(domino, 2)
+            .blocked([(0, 0), (1, 2)])
+            .fill(Fill::Exact);
+        assert_eq!(blocked.count_solutions(None), 1);
+    }
+
+    #[test]
+    fn renders_solutions() {
+        let packing = Packing::new(2, 2)
+            .piece(Polyomino::parse("##"), 1)
+            .symmetry(Symmetry::Fixed);
+        assert_eq!(packing.count_solutions(None), 2);
+
+        let rendered = packing.first_solution().unwrap().render();
+        assert!(rendered == "AA\n..\n" || rendered == "..\nAA\n");
+    }
+
+    #[test]
+    fn checks_solutions() {
+        let packing = Packing::new(3, 2).piece(l_tromino(), 2);
+        assert_eq!(packing.check(&packing.first_solution().unwrap()), Ok(()));
+
+        let solution = |placements: Vec<Vec<(usize, usize)>>| Solution {
+            width: 3,
+            height: 2,
+            placements: placements
+                .into_iter()
+                .map(|cells| Placement { piece: 0, cells })
+                .collect(),
+        };
+        let valid = solution(vec![
+            vec![(0, 0), (1, 0), (1, 1)],
+            vec![(0, 1), (0, 2), (1, 2)],
+        ]);
+        assert_eq!(packing.check(&valid), Ok(()));
+
+        let missing = solution(vec![vec![(0, 0), (1, 0), (1, 1)]]);
+        assert!(
+            packing
+                .check(&missing)
+                .unwrap_err()
+                .contains("placed 1 times")
+        );
+        let overlapping = solution(vec![
+            vec![(0, 0), (1, 0), (1, 1)],
+            vec![(0, 0), (0, 1), (1, 1)],
+        ]);
+        assert!(
+            packing
+                .check(&overlapping)
+                .unwrap_err()
+                .contains("overlaps")
+        );
+        let straight = solution(vec![
+            vec![(0, 0), (0, 1), (0, 2)],
+            vec![(1, 0), (1, 1), (1, 2)],
+        ]);
+        assert!(
+            packing
+                .check(&straight)
+                .unwrap_err()
+                .contains("orientation")
+        );
+
+        let fixed = packing.clone().symmetry(Symmetry::Fixed);
+        assert!(fixed.check(&valid).unwrap_err().contains("orientation"));
+    }
+}
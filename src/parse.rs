@@ -0,0 +1,647 @@
+//! Input parsing helpers: integer extraction, paragraph splitting, columnar layouts, and a small
+//! cursor for structured lines that reports the position of the first error.
+//!
+//! The integer iterators work on bytes and never allocate. They skip everything that is
+//! not a digit, so `"3-5, x=12"` yields `3, 5, 12` unsigned and `3, -5, 12` signed.
+
+use std::fmt::Display;
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+/// Integer types that can be built from decimal digits.
+pub trait Digits: Copy {
+    const ZERO: Self;
+
+    /// `self * 10 + digit`, or `None` on overflow.
+    fn push_digit(self, digit: u8) -> Option<Self>;
+}
+
+/// Integer types with negative values, for [`signed_ints`].
+pub trait Signed: Digits {
+    /// `self * 10 - digit`, or `None` on overflow. Reads the digits of negative numbers, so that
+    /// the most negative value fits.
+    fn push_negative_digit(self, digit: u8) -> Option<Self>;
+}
+
+macro_rules! impl_digits {
+    ($($t:ty),*) => {
+        $(
+            impl Digits for $t {
+                const ZERO: Self = 0;
+
+                fn push_digit(self, digit: u8) -> Option<Self> {
+                    self.checked_mul(10)?.checked_add(digit as $t)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Signed for $t {
+                fn push_negative_digit(self, digit: u8) -> Option<Self> {
+                    self.checked_mul(10)?.checked_sub(digit as $t)
+                }
+            }
+        )*
+    };
+}
+
+impl_digits!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+impl_signed!(i8, i16, i32, i64, i128, isize);
+
+/// Adds a digit to a value, see [`Digits::push_digit`] and [`Signed::push_negative_digit`].
+type PushDigit<T> = fn(T, u8) -> Option<T>;
+
+/// Reads the digits at the start of `bytes`, returning the value and the number of digits.
+/// The value is `None` if it overflows `T`, the number of digits still covers all of them.
+fn read_digits<T: Digits>(bytes: &[u8], push: PushDigit<T>) -> (Option<T>, usize) {
+    let mut value = Some(T::ZERO);
+    let mut len = 0;
+    while let Some(&b) = bytes.get(len).filter(|b| b.is_ascii_digit()) {
+        value = value.and_then(|value| push(value, b - b'0'));
+        len += 1;
+    }
+    (value, len)
+}
+
+/// An iterator over the integers in a byte string. See [`unsigned_ints`] and [`signed_ints`].
+#[derive(Clone, Debug)]
+pub struct Ints<'a, T> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Set for signed iteration: reads numbers directly preceded by `-`.
+    push_negative: Option<PushDigit<T>>,
+}
+
+impl<T: Digits> Iterator for Ints<'_, T> {
+    type Item = T;
+
+    /// The next integer, skipping those that don't fit `T`.
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let start = self.pos + self.bytes[self.pos..].iter().position(u8::is_ascii_digit)?;
+            let push = match self.push_negative {
+                Some(push) if start > 0 && self.bytes[start - 1] == b'-' => push,
+                _ => T::push_digit,
+            };
+            let (value, len) = read_digits(&self.bytes[start..], push);
+            self.pos = start + len;
+            if value.is_some() {
+                return value;
+            }
+        }
+    }
+}
+
+impl<T: Digits> FusedIterator for Ints<'_, T> {}
+
+/// Iterates over the runs of digits in `input` as non-negative integers, skipping those that
+/// overflow `T`.
+pub fn unsigned_ints<T: Digits>(input: &(impl AsRef<[u8]> + ?Sized)) -> Ints<'_, T> {
+    Ints {
+        bytes: input.as_ref(),
+        pos: 0,
+        push_negative: None,
+    }
+}
+
+/// Iterates over the integers in `input`, treating a `-` directly before digits as a sign and
+/// skipping those that overflow `T`.
+pub fn signed_ints<T: Signed>(input: &(impl AsRef<[u8]> + ?Sized)) -> Ints<'_, T> {
+    Ints {
+        bytes: input.as_ref(),
+        pos: 0,
+        push_negative: Some(T::push_negative_digit),
+    }
+}
+
+/// Splits `input` into blocks separated by blank lines, skipping empty blocks.
+/// Lines keep their original content, only the surrounding newlines are trimmed.
+pub fn paragraphs(input: &str) -> impl Iterator<Item = &str> {
+    let mut rest = input;
+    std::iter::from_fn(move || {
+        // Skip leading blank lines.
+        loop {
+            let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            if line_end == 0 || !rest[..line_end].trim().is_empty() {
+                break;
+            }
+            rest = &rest[line_end..];
+        }
+        if rest.is_empty() {
+            return None;
+        }
+
+        // The paragraph ends before the next blank line.
+        let mut end = 0;
+        while end < rest.len() {
+            let line_end = rest[end..].find('\n').map_or(rest.len(), |i| end + i + 1);
+            if rest[end..line_end].trim().is_empty() {
+                break;
+            }
+            end = line_end;
+        }
+
+        let paragraph = rest[..end].trim_end_matches(['\n', '\r']);
+        rest = &rest[end..];
+        Some(paragraph)
+    })
+}
+
+/// Finds blocks of columns separated by columns that are blank in every line, e.g. the
+/// problems of a worksheet laid out side by side. Shorter lines count as padded with spaces.
+///
+/// Returns byte ranges, so the lines should be ASCII.
+pub fn column_blocks(lines: &[&str]) -> Vec<Range<usize>> {
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let blank = |col: usize| {
+        lines.iter().all(|l| {
+            l.as_bytes()
+                .get(col)
+                .is_none_or(|b| b.is_ascii_whitespace())
+        })
+    };
+
+    let mut blocks = Vec::new();
+    let mut start = None;
+    for col in 0..=width {
+        match (start, col == width || blank(col)) {
+            (None, false) => start = Some(col),
+            (Some(s), true) => {
+                blocks.push(s..col);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// The part of `line` within `columns`, clamped to the length of the line.
+pub fn column_slice(line: &str, columns: Range<usize>) -> &str {
+    let end = columns.end.min(line.len());
+    &line[columns.start.min(end)..end]
+}
+
+/// How lines of different lengths line up in a [`ColumnLayout`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Lines start in the same column, shorter ones lack trailing spaces.
+    #[default]
+    Left,
+    /// Lines end in the same column, shorter ones lack leading spaces.
+    Right,
+}
+
+/// Text laid out in blocks side by side, separated by columns that are blank in every line,
+/// e.g. the problems of a worksheet. Blank lines are skipped and shorter lines padded with
+/// spaces according to their [`Align`]ment, so every row has the same width.
+///
+/// Works on bytes, so the lines should be ASCII.
+#[derive(Clone, Debug)]
+pub struct ColumnLayout {
+    rows: Vec<String>,
+    blocks: Vec<Range<usize>>,
+}
+
+impl ColumnLayout {
+    pub fn new(input: &str, align: Align) -> Self {
+        let lines: Vec<&str> = input.lines().filter(|l| !l.trim().is_empty()).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let rows: Vec<String> = lines
+            .iter()
+            .map(|line| match align {
+                Align::Left => format!("{line:<width$}"),
+                Align::Right => format!("{line:>width$}"),
+            })
+            .collect();
+        let blocks = column_blocks(&rows.iter().map(String::as_str).collect::<Vec<_>>());
+        Self { rows, blocks }
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The number of columns of every row.
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, String::len)
+    }
+
+    /// The blocks from left to right.
+    pub fn blocks(&self) -> impl DoubleEndedIterator<Item = Block<'_>> + ExactSizeIterator {
+        self.blocks.iter().map(|columns| Block {
+            rows: &self.rows,
+            columns: columns.clone(),
+        })
+    }
+}
+
+/// A block of a [`ColumnLayout`], readable row by row or column by column.
+#[derive(Clone, Debug)]
+pub struct Block<'a> {
+    rows: &'a [String],
+    columns: Range<usize>,
+}
+
+impl<'a> Block<'a> {
+    /// The columns of the layout the block spans.
+    pub fn span(&self) -> Range<usize> {
+        self.columns.clone()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The block restricted to some of its rows, e.g. to read a header or footer separately.
+    pub fn select_rows(&self, rows: Range<usize>) -> Self {
+        Self {
+            rows: &self.rows[rows],
+            columns: self.columns.clone(),
+        }
+    }
+
+    /// The text of each row from top to bottom, padded to the width of the block.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a str> + ExactSizeIterator {
+        let columns = self.columns.clone();
+        self.rows.iter().map(move |row| &row[columns.clone()])
+    }
+
+    /// The text of each column from left to right, read top to bottom.
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = String> + ExactSizeIterator + 'a {
+        let rows = self.rows;
+        self.columns
+            .clone()
+            .map(move |col| rows.iter().map(|row| row.as_bytes()[col] as char).collect())
+    }
+}
+
+/// An error from [`Cursor`]: what was expected, and at which byte offset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub pos: usize,
+    pub expected: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {} at byte {}", self.expected, self.pos)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// A position in a string with methods that consume input or fail with a [`ParseError`].
+/// Methods that fail leave the cursor where it was.
+#[derive(Clone, Copy, Debug)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// The byte offset of the cursor in the input.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The input that has not been consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// An error at the current position.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            pos: self.pos,
+            expected: expected.into(),
+        }
+    }
+
+    /// Consumes and returns the next character.
+    pub fn next_char(&mut self) -> ParseResult<char> {
+        let c = self.peek().ok_or_else(|| self.error("a character"))?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    /// Consumes `prefix` if the input starts with it.
+    pub fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+        if found {
+            self.pos += prefix.len();
+        }
+        found
+    }
+
+    /// Consumes `prefix`, or fails if the input does not start with it.
+    pub fn expect(&mut self, prefix: &str) -> ParseResult<()> {
+        if self.eat(prefix) {
+            Ok(())
+        } else {
+            Err(self.error(format!("`{prefix}`")))
+        }
+    }
+
+    /// Consumes characters while `predicate` holds and returns them.
+    pub fn take_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Consumes everything up to `delimiter` and the delimiter itself, returning the part before it.
+    pub fn until(&mut self, delimiter: &str) -> ParseResult<&'a str> {
+        let len = self
+            .rest()
+            .find(delimiter)
+            .ok_or_else(|| self.error(format!("`{delimiter}`")))?;
+        let taken = &self.rest()[..len];
+        self.pos += len + delimiter.len();
+        Ok(taken)
+    }
+
+    /// Parses a non-negative integer.
+    pub fn unsigned<T: Digits>(&mut self) -> ParseResult<T> {
+        self.digits(T::push_digit)
+    }
+
+    /// Parses digits with `push`, failing if there are none or their value overflows `T`.
+    fn digits<T: Digits>(&mut self, push: PushDigit<T>) -> ParseResult<T> {
+        match read_digits(self.rest().as_bytes(), push) {
+            (_, 0) => Err(self.error("a digit")),
+            (None, _) => Err(self.error(format!(
+                "an integer that fits {}",
+                std::any::type_name::<T>()
+            ))),
+            (Some(value), len) => {
+                self.pos += len;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Parses an integer with an optional leading `-` or `+`.
+    pub fn signed<T: Signed>(&mut self) -> ParseResult<T> {
+        let start = *self;
+        let negative = self.eat("-");
+        if !negative {
+            self.eat("+");
+        }
+        let push = if negative {
+            T::push_negative_digit
+        } else {
+            T::push_digit
+        };
+        self.digits(push).inspect_err(|_| *self = start)
+    }
+
+    /// Parses `open`, then `inner`, then `close`.
+    pub fn delimited<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        inner: impl FnOnce(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        let start = *self;
+        let result = self
+            .expect(open)
+            .and_then(|()| inner(self))
+            .and_then(|value| self.expect(close).map(|()| value));
+        if result.is_err() {
+            *self = start;
+        }
+        result
+    }
+
+    /// Parses one or more items separated by `separator`.
+    pub fn separated<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let start = *self;
+        let mut items = Vec::new();
+        loop {
+            match item(self) {
+                Ok(value) => items.push(value),
+                Err(e) => {
+                    *self = start;
+                    return Err(e);
+                }
+            }
+            if !self.eat(separator) {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Applies `item` as often as it succeeds, skipping whitespace before each attempt. Stops
+    /// at the first `item` that fails or succeeds without consuming anything, e.g. a `take_while`
+    /// at a character it doesn't take, which would otherwise match forever.
+    pub fn many<T>(&mut self, mut item: impl FnMut(&mut Self) -> ParseResult<T>) -> Vec<T> {
+        let mut items = Vec::new();
+        loop {
+            let before = *self;
+            self.skip_whitespace();
+            let start = self.pos;
+            match item(self) {
+                Ok(value) if self.pos > start => items.push(value),
+                _ => {
+                    *self = before;
+                    return items;
+                }
+            }
+        }
+    }
+
+    /// Fails unless all input apart from trailing whitespace has been consumed.
+    pub fn finish(mut self) -> ParseResult<()> {
+        self.skip_whitespace();
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of input"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Align, ColumnLayout, Cursor, ParseError, column_blocks, column_slice, paragraphs,
+        signed_ints, unsigned_ints,
+    };
+
+    #[test]
+    fn extracts_integers() {
+        let unsigned: Vec<u64> = unsigned_ints("3-5, x=12\n7").collect();
+        assert_eq!(unsigned, vec![3, 5, 12, 7]);
+
+        let signed: Vec<i32> = signed_ints("p=-3,4 v=12--7").collect();
+        assert_eq!(signed, vec![-3, 4, 12, -7]);
+
+        let bytes: Vec<u8> = unsigned_ints(b"no 255 numbers".as_slice()).collect();
+        assert_eq!(bytes, vec![255]);
+        assert_eq!(unsigned_ints::<u32>("none").next(), None);
+    }
+
+    #[test]
+    fn rejects_integers_that_overflow() {
+        let bytes: Vec<u8> = unsigned_ints("300 255 256 0").collect();
+        assert_eq!(bytes, vec![255, 0]);
+        let signed: Vec<i8> = signed_ints("-128 -129 127 128").collect();
+        assert_eq!(signed, vec![-128, 127]);
+
+        let mut c = Cursor::new("300");
+        assert_eq!(
+            c.unsigned::<u8>(),
+            Err(ParseError {
+                pos: 0,
+                expected: "an integer that fits u8".into()
+            })
+        );
+        assert_eq!(c.pos(), 0);
+        assert_eq!(c.unsigned::<u16>(), Ok(300));
+
+        let mut c = Cursor::new("-129,-128");
+        assert!(c.signed::<i8>().is_err());
+        assert_eq!(c.pos(), 0);
+        assert_eq!(c.signed::<i16>(), Ok(-129));
+        assert!(c.eat(","));
+        assert_eq!(c.signed::<i8>(), Ok(-128));
+    }
+
+    #[test]
+    fn splits_paragraphs() {
+        let input = "\na\nb\n\n\r\nc\n  \n\nd\n";
+        assert_eq!(
+            paragraphs(input).collect::<Vec<_>>(),
+            vec!["a\nb", "c", "d"]
+        );
+        assert_eq!(
+            paragraphs("x\r\ny\r\n\r\nz").collect::<Vec<_>>(),
+            vec!["x\r\ny", "z"]
+        );
+        assert_eq!(paragraphs("\n\n").count(), 0);
+    }
+
+    #[test]
+    fn splits_columns() {
+        let lines = ["123 328  51", " 45 64  387", "*   +   *"];
+        let blocks = column_blocks(&lines);
+        assert_eq!(blocks, vec![0..3, 4..7, 8..11]);
+        assert_eq!(column_slice(lines[2], blocks[2].clone()), "*");
+        assert_eq!(column_slice(lines[1], blocks[0].clone()), " 45");
+    }
+
+    #[test]
+    fn lays_out_columns() {
+        // Ragged lines, and a blank line that is skipped
+        let input = "123 328  51 64\n 45 64  387 23\n\n*   +   *   +\n";
+        let layout = ColumnLayout::new(input, Align::Left);
+        assert_eq!((layout.width(), layout.height()), (14, 3));
+        let spans: Vec<_> = layout.blocks().map(|block| block.span()).collect();
+        assert_eq!(spans, vec![0..3, 4..7, 8..11, 12..14]);
+
+        let block = layout.blocks().nth(2).unwrap();
+        assert_eq!(block.rows().collect::<Vec<_>>(), vec![" 51", "387", "*  "]);
+        let numbers = block.select_rows(0..2);
+        assert_eq!(
+            numbers.columns().collect::<Vec<_>>(),
+            vec![" 3", "58", "17"]
+        );
+        let last = layout.blocks().last().unwrap();
+        assert_eq!(last.rows().collect::<Vec<_>>(), vec!["64", "23", "+ "]);
+
+        // Right-aligned lines keep their last columns together instead
+        let rows = |layout: ColumnLayout| -> Vec<Vec<String>> {
+            let blocks = layout
+                .blocks()
+                .map(|b| b.rows().map(String::from).collect());
+            blocks.collect()
+        };
+        let layout = ColumnLayout::new("1 22\n  3\n", Align::Right);
+        assert_eq!(rows(layout), vec![vec!["1", " "], vec!["22", " 3"]]);
+        let layout = ColumnLayout::new("1 22\n  3\n", Align::Left);
+        assert_eq!(rows(layout), vec![vec!["1", " "], vec!["22", "3 "]]);
+    }
+
+    #[test]
+    fn parses_structured_lines() {
+        let mut c = Cursor::new("[.#] (3) (1,3) {3,-5}");
+        let lights = c
+            .delimited("[", "]", |c| Ok(c.take_while(|ch| ch != ']')))
+            .unwrap();
+        assert_eq!(lights, ".#");
+        let buttons =
+            c.many(|c| c.delimited("(", ")", |c| c.separated(",", Cursor::unsigned::<usize>)));
+        assert_eq!(buttons, vec![vec![3], vec![1, 3]]);
+        c.skip_whitespace();
+        let jolts = c
+            .delimited("{", "}", |c| c.separated(",", Cursor::signed::<i64>))
+            .unwrap();
+        assert_eq!(jolts, vec![3, -5]);
+        assert!(c.finish().is_ok());
+    }
+
+    #[test]
+    fn stops_repeating_items_that_consume_nothing() {
+        let mut c = Cursor::new("ab cd ;ef");
+        let words = c.many(|c| Ok(c.take_while(|ch| ch.is_ascii_alphabetic())));
+        assert_eq!(words, vec!["ab", "cd"]);
+        assert_eq!(c.pos(), 5);
+        assert!(c.many(|c| Ok(c.take_while(char::is_alphabetic))).is_empty());
+        assert_eq!(c.pos(), 5);
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let mut c = Cursor::new("12,x4");
+        let err = c.separated(",", Cursor::unsigned::<u32>).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                pos: 3,
+                expected: "a digit".into()
+            }
+        );
+        assert_eq!(c.pos(), 0);
+        assert_eq!(err.to_string(), "expected a digit at byte 3");
+
+        let mut c = Cursor::new("L68 extra");
+        assert_eq!(c.next_char(), Ok('L'));
+        assert_eq!(c.unsigned::<u32>(), Ok(68));
+        assert_eq!(c.finish().unwrap_err().pos, 4);
+    }
+}
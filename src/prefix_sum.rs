@@ -0,0 +1,225 @@
+//! Prefix sums over 1D, 2D and 3D arrays for constant-time range sums.
+//!
+//! Queries take any [`RangeBounds<usize>`] per axis, e.g. `2..5`, `1..=3` or `..`.
+
+use crate::grid::Grid;
+use std::ops::{Add, Bound, Range, RangeBounds, Sub};
+
+/// Values that can be summed by a prefix sum.
+pub trait Summable: Copy + Default + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> Summable for T {}
+
+/// Converts `range` into a half-open range within `0..len`.
+///
+/// # Panics
+/// If the range extends past `len` or starts after it ends.
+fn resolve(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&x) => x + 1,
+        Bound::Excluded(&x) => x,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "range {start}..{end} out of bounds for length {len}"
+    );
+    start..end
+}
+
+#[derive(Clone, Debug)]
+pub struct PrefixSum1D<T> {
+    /// sums[i]: the sum of the first `i` values.
+    sums: Vec<T>,
+}
+
+impl<T: Summable> PrefixSum1D<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut sums = vec![T::default()];
+        for value in values {
+            sums.push(*sums.last().unwrap() + value);
+        }
+        Self { sums }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sums.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let r = resolve(range, self.len());
+        self.sums[r.end] - self.sums[r.start]
+    }
+}
+
+/// Prefix sums over a `height` x `width` array, indexed like [`Grid`] as `(row, col)`.
+#[derive(Clone, Debug)]
+pub struct PrefixSum2D<T> {
+    width: usize,
+    height: usize,
+    /// sums[r * (width + 1) + c]: the sum over rows `..r` and columns `..c`.
+    sums: Vec<T>,
+}
+
+impl<T: Summable> PrefixSum2D<T> {
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let stride = width + 1;
+        let mut sums = vec![T::default(); stride * (height + 1)];
+        for r in 0..height {
+            for c in 0..width {
+                sums[(r + 1) * stride + c + 1] =
+                    sums[r * stride + c + 1] + sums[(r + 1) * stride + c] - sums[r * stride + c]
+                        + f(r, c);
+            }
+        }
+        Self {
+            width,
+            height,
+            sums,
+        }
+    }
+
+    /// Prefix sums over the values `f` assigns to each cell of `grid`.
+    pub fn from_grid<U>(grid: &Grid<U>, mut f: impl FnMut(&U) -> T) -> Self {
+        Self::from_fn(grid.width(), grid.height(), |r, c| f(&grid[(r, c)]))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The sum over the cells in `rows` x `cols`.
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let (r, c) = (resolve(rows, self.height), resolve(cols, self.width));
+        let at = |r: usize, c: usize| self.sums[r * (self.width + 1) + c];
+        // Adding before subtracting keeps intermediate values non-negative for unsigned types.
+        at(r.end, c.end) + at(r.start, c.start) - at(r.start, c.end) - at(r.end, c.start)
+    }
+}
+
+/// Prefix sums over an `nx` x `ny` x `nz` array, indexed as `(x, y, z)`.
+#[derive(Clone, Debug)]
+pub struct PrefixSum3D<T> {
+    dims: (usize, usize, usize),
+    sums: Vec<T>,
+}
+
+impl<T: Summable> PrefixSum3D<T> {
+    pub fn from_fn(
+        (nx, ny, nz): (usize, usize, usize),
+        mut f: impl FnMut(usize, usize, usize) -> T,
+    ) -> Self {
+        let idx = |x: usize, y: usize, z: usize| (x * (ny + 1) + y) * (nz + 1) + z;
+        let mut sums = vec![T::default(); (nx + 1) * (ny + 1) * (nz + 1)];
+        for x in 0..nx {
+            for y in 0..ny {
+                for z in 0..nz {
+                    // Inclusion-exclusion over the seven smaller prefixes.
+                    sums[idx(x + 1, y + 1, z + 1)] = f(x, y, z)
+                        + sums[idx(x, y + 1, z + 1)]
+                        + sums[idx(x + 1, y, z + 1)]
+                        + sums[idx(x + 1, y + 1, z)]
+                        + sums[idx(x, y, z)]
+                        - sums[idx(x, y, z + 1)]
+                        - sums[idx(x, y + 1, z)]
+                        - sums[idx(x + 1, y, z)];
+                }
+            }
+        }
+        Self {
+            dims: (nx, ny, nz),
+            sums,
+        }
+    }
+
+    pub fn dims(&self) -> (usize, usize, usize) {
+        self.dims
+    }
+
+    /// The sum over the cells in `xs` x `ys` x `zs`.
+    pub fn sum(
+        &self,
+        xs: impl RangeBounds<usize>,
+        ys: impl RangeBounds<usize>,
+        zs: impl RangeBounds<usize>,
+    ) -> T {
+        let (nx, ny, nz) = self.dims;
+        let (x, y, z) = (resolve(xs, nx), resolve(ys, ny), resolve(zs, nz));
+        let at = |x: usize, y: usize, z: usize| self.sums[(x * (ny + 1) + y) * (nz + 1) + z];
+        let added = at(x.end, y.end, z.end)
+            + at(x.start, y.start, z.end)
+            + at(x.start, y.end, z.start)
+            + at(x.end, y.start, z.start);
+        let removed = at(x.start, y.end, z.end)
+            + at(x.end, y.start, z.end)
+            + at(x.end, y.end, z.start)
+            + at(x.start, y.start, z.start);
+        added - removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrefixSum1D, PrefixSum2D, PrefixSum3D};
+    use crate::grid::Grid;
+
+    #[test]
+    fn sums_ranges_in_1d() {
+        let sums = PrefixSum1D::new([3_i64, -1, 4, 1, 5]);
+        assert_eq!(sums.len(), 5);
+        assert_eq!(sums.sum(..), 12);
+        assert_eq!(sums.sum(1..3), 3);
+        assert_eq!(sums.sum(1..=3), 4);
+        assert_eq!(sums.sum(2..2), 0);
+    }
+
+    #[test]
+    fn sums_rectangles_in_2d() {
+        let grid = Grid::parse("#..#\n.##.\n#.##", |c| c == '#');
+        let sums = PrefixSum2D::from_grid(&grid, |&b| u32::from(b));
+        assert_eq!(sums.sum(.., ..), 7);
+        assert_eq!(sums.sum(1..=2, 1..=2), 3);
+        assert_eq!(sums.sum(0..1, ..), 2);
+        assert_eq!(sums.sum(.., 3..), 2);
+        assert_eq!(sums.sum(1..1, ..), 0);
+    }
+
+    #[test]
+    fn sums_boxes_in_3d() {
+        let sums = PrefixSum3D::from_fn((3, 4, 5), |x, y, z| x * 100 + y * 10 + z);
+        let brute =
+            |xs: std::ops::Range<usize>, ys: std::ops::Range<usize>, zs: std::ops::Range<usize>| {
+                let mut total = 0;
+                for x in xs {
+                    for y in ys.clone() {
+                        for z in zs.clone() {
+                            total += x * 100 + y * 10 + z;
+                        }
+                    }
+                }
+                total
+            };
+        assert_eq!(sums.sum(.., .., ..), brute(0..3, 0..4, 0..5));
+        assert_eq!(sums.sum(1..3, 2..4, 1..4), brute(1..3, 2..4, 1..4));
+        assert_eq!(sums.sum(2..=2, 0..=0, 4..=4), 204);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn rejects_out_of_bounds_ranges() {
+        PrefixSum1D::new([1, 2, 3]).sum(0..4);
+    }
+}
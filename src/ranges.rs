@@ -0,0 +1,308 @@
+//! Sets of integers stored as sorted, disjoint intervals.
+//!
+//! Intervals are stored with inclusive bounds, so sets may reach up to `T::MAX`.
+//! Inputs accept any [`RangeBounds`], e.g. `3..7`, `3..=6` or `..10`.
+
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+/// Integer types that can be stored in an [`IntervalSet`].
+pub trait Integer: Copy + Ord + Debug {
+    const MIN: Self;
+    const MAX: Self;
+
+    /// The next larger value, if there is one.
+    fn succ(self) -> Option<Self>;
+
+    /// The next smaller value, if there is one.
+    fn pred(self) -> Option<Self>;
+
+    /// The number of values in `lo..=hi`, saturating at `u128::MAX`.
+    fn span(lo: Self, hi: Self) -> u128;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                #[allow(clippy::cast_sign_loss, clippy::cast_lossless)]
+                fn span(lo: Self, hi: Self) -> u128 {
+                    // Two's complement wrapping gives the distance for signed types as well.
+                    ((hi as i128).wrapping_sub(lo as i128) as u128).saturating_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl Integer for u128 {
+    const MIN: Self = u128::MIN;
+    const MAX: Self = u128::MAX;
+
+    fn succ(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    fn pred(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    fn span(lo: Self, hi: Self) -> u128 {
+        (hi - lo).saturating_add(1)
+    }
+}
+
+/// Converts any range into inclusive bounds, or `None` if it is empty.
+fn to_inclusive<T: Integer>(range: impl RangeBounds<T>) -> Option<(T, T)> {
+    let lo = match range.start_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x.succ()?,
+        Bound::Unbounded => T::MIN,
+    };
+    let hi = match range.end_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x.pred()?,
+        Bound::Unbounded => T::MAX,
+    };
+    (lo <= hi).then_some((lo, hi))
+}
+
+/// Whether an interval ending at `hi` overlaps or is directly followed by one starting at `lo`.
+fn touches<T: Integer>(hi: T, lo: T) -> bool {
+    lo.pred().is_none_or(|p| hi >= p)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    /// Sorted, disjoint and non-adjacent inclusive intervals.
+    intervals: Vec<(T, T)>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds all values in `range`, merging it with overlapping or adjacent intervals.
+    pub fn insert(&mut self, range: impl RangeBounds<T>) {
+        let Some((lo, hi)) = to_inclusive(range) else {
+            return;
+        };
+
+        let start = self.intervals.partition_point(|&(_, h)| !touches(h, lo));
+        let end = self.intervals.partition_point(|&(l, _)| touches(hi, l));
+
+        let merged = if start < end {
+            (
+                lo.min(self.intervals[start].0),
+                hi.max(self.intervals[end - 1].1),
+            )
+        } else {
+            (lo, hi)
+        };
+        self.intervals.splice(start..end, [merged]);
+    }
+
+    /// Removes all values in `range`.
+    pub fn remove(&mut self, range: impl RangeBounds<T>) {
+        if let Some((lo, hi)) = to_inclusive(range) {
+            *self = self.difference(&Self {
+                intervals: vec![(lo, hi)],
+            });
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.intervals.partition_point(|&(l, _)| l <= value);
+        i > 0 && self.intervals[i - 1].1 >= value
+    }
+
+    /// Whether every value of `range` is in the set. Empty ranges are always contained.
+    pub fn contains_range(&self, range: impl RangeBounds<T>) -> bool {
+        let Some((lo, hi)) = to_inclusive(range) else {
+            return true;
+        };
+        let i = self.intervals.partition_point(|&(l, _)| l <= lo);
+        i > 0 && self.intervals[i - 1].1 >= hi
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The number of disjoint intervals.
+    pub fn interval_count(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// The number of values in the set, saturating at `u128::MAX`.
+    pub fn total_len(&self) -> u128 {
+        self.intervals
+            .iter()
+            .fold(0_u128, |acc, &(lo, hi)| acc.saturating_add(T::span(lo, hi)))
+    }
+
+    /// Iterates over the disjoint intervals in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.intervals.iter().map(|&(lo, hi)| lo..=hi)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for &(lo, hi) in &other.intervals {
+            result.insert(lo..=hi);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a_lo, a_hi) = self.intervals[i];
+            let (b_lo, b_hi) = other.intervals[j];
+            let (lo, hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+            if lo <= hi {
+                intervals.push((lo, hi));
+            }
+            if a_hi < b_hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { intervals }
+    }
+
+    /// The values of `self` that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let mut j = 0;
+
+        for &(lo, hi) in &self.intervals {
+            let mut lo = Some(lo);
+            // Skip intervals of `other` that end before this one starts.
+            while j < other.intervals.len() && other.intervals[j].1 < lo.unwrap() {
+                j += 1;
+            }
+
+            let mut k = j;
+            while let Some(start) = lo
+                && k < other.intervals.len()
+                && other.intervals[k].0 <= hi
+            {
+                let (cut_lo, cut_hi) = other.intervals[k];
+                if start < cut_lo {
+                    intervals.push((start, cut_lo.pred().unwrap()));
+                }
+                lo = if cut_hi < hi { cut_hi.succ() } else { None };
+                k += 1;
+            }
+
+            if let Some(start) = lo {
+                intervals.push((start, hi));
+            }
+        }
+
+        Self { intervals }
+    }
+}
+
+impl<T: Integer, R: RangeBounds<T>> FromIterator<R> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Integer, R: RangeBounds<T>> Extend<R> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+
+    fn intervals<T: super::Integer>(set: &IntervalSet<T>) -> Vec<(T, T)> {
+        set.iter().map(|r| (*r.start(), *r.end())).collect()
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_ranges() {
+        let set: IntervalSet<u64> = [3..=5, 10..=14, 16..=20, 12..=18, 6..=6]
+            .into_iter()
+            .collect();
+        assert_eq!(intervals(&set), vec![(3, 6), (10, 20)]);
+        assert_eq!(set.total_len(), 15);
+        assert_eq!(set.interval_count(), 2);
+    }
+
+    #[test]
+    fn accepts_exclusive_and_unbounded_ranges() {
+        let mut set = IntervalSet::<u8>::new();
+        set.insert(10..20);
+        set.insert(250..);
+        set.insert(5..5);
+        assert_eq!(intervals(&set), vec![(10, 19), (250, 255)]);
+        assert!(set.contains(255));
+
+        let full: IntervalSet<u128> = [..].into_iter().collect::<IntervalSet<u128>>();
+        assert_eq!(full.total_len(), u128::MAX);
+
+        let signed: IntervalSet<i32> = [-5..=5].into_iter().collect();
+        assert_eq!(signed.total_len(), 11);
+    }
+
+    #[test]
+    fn answers_containment_queries() {
+        let set: IntervalSet<i64> = [1..=3, 7..=9].into_iter().collect();
+        assert!(set.contains(1) && set.contains(8));
+        assert!(!set.contains(0) && !set.contains(5) && !set.contains(10));
+        assert!(set.contains_range(7..10));
+        assert!(!set.contains_range(3..=7));
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a: IntervalSet<u32> = [0..=10, 20..=30].into_iter().collect();
+        let b: IntervalSet<u32> = [5..=25].into_iter().collect();
+
+        assert_eq!(intervals(&a.union(&b)), vec![(0, 30)]);
+        assert_eq!(intervals(&a.intersection(&b)), vec![(5, 10), (20, 25)]);
+        assert_eq!(intervals(&a.difference(&b)), vec![(0, 4), (26, 30)]);
+        assert_eq!(intervals(&b.difference(&a)), vec![(11, 19)]);
+
+        let mut c = a.clone();
+        c.remove(3..=4);
+        c.remove(30..);
+        assert_eq!(intervals(&c), vec![(0, 2), (5, 10), (20, 29)]);
+    }
+}
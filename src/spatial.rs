@@ -0,0 +1,301 @@
+//! A k-d tree over integer points for nearest-neighbour queries by squared Euclidean distance.
+//!
+//! Neighbours are found lazily in order of increasing distance, by a best-first search over the
+//! tree. [`KdTree::closest_pairs`] builds on that to stream the pairs of points closest to each
+//! other, without materialising all `n²` of them.
+
+use crate::geom::{Point2, Point3};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+/// Points with `D` integer coordinates.
+pub trait Coords<const D: usize>: Copy {
+    fn coords(self) -> [i64; D];
+}
+
+impl<const D: usize> Coords<D> for [i64; D] {
+    fn coords(self) -> [i64; D] {
+        self
+    }
+}
+
+impl Coords<2> for Point2 {
+    fn coords(self) -> [i64; 2] {
+        [self.x, self.y]
+    }
+}
+
+impl Coords<3> for Point3 {
+    fn coords(self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+pub fn squared_distance<const D: usize>(a: [i64; D], b: [i64; D]) -> i64 {
+    a.iter().zip(&b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+/// Nodes with at most this many points are not split any further.
+const LEAF_SIZE: usize = 8;
+
+#[derive(Clone, Debug)]
+struct Node<const D: usize> {
+    /// The bounding box of the points of the node.
+    lo: [i64; D],
+    hi: [i64; D],
+    /// The points of the node, as a range of `KdTree::order`.
+    range: Range<usize>,
+    children: Option<[usize; 2]>,
+}
+
+impl<const D: usize> Node<D> {
+    /// The squared distance from `point` to the bounding box, a lower bound for every point inside.
+    fn distance_to(&self, point: [i64; D]) -> i64 {
+        (0..D)
+            .map(|axis| {
+                let d = (self.lo[axis] - point[axis])
+                    .max(point[axis] - self.hi[axis])
+                    .max(0);
+                d * d
+            })
+            .sum()
+    }
+}
+
+/// A k-d tree over a fixed set of points, which keep their indices.
+#[derive(Clone, Debug)]
+pub struct KdTree<const D: usize> {
+    points: Vec<[i64; D]>,
+    /// Point indices, ordered so that every node covers a contiguous range.
+    order: Vec<usize>,
+    nodes: Vec<Node<D>>,
+}
+
+impl<const D: usize> KdTree<D> {
+    /// Builds the tree in `O(n log n)`, splitting each node at the median of its widest axis.
+    pub fn new(points: impl IntoIterator<Item = impl Coords<D>>) -> Self {
+        let points: Vec<[i64; D]> = points.into_iter().map(Coords::coords).collect();
+        let mut tree = Self {
+            order: (0..points.len()).collect(),
+            points,
+            nodes: vec![],
+        };
+        if !tree.points.is_empty() {
+            tree.build(0..tree.points.len());
+        }
+        tree
+    }
+
+    /// Adds the node for `range` of `order` and its descendants, and returns its index.
+    fn build(&mut self, range: Range<usize>) -> usize {
+        let mut lo = [i64::MAX; D];
+        let mut hi = [i64::MIN; D];
+        for &i in &self.order[range.clone()] {
+            for axis in 0..D {
+                lo[axis] = lo[axis].min(self.points[i][axis]);
+                hi[axis] = hi[axis].max(self.points[i][axis]);
+            }
+        }
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            lo,
+            hi,
+            range: range.clone(),
+            children: None,
+        });
+
+        if range.len() > LEAF_SIZE {
+            let axis = (0..D).max_by_key(|&axis| hi[axis] - lo[axis]).unwrap();
+            let half = range.len() / 2;
+            let points = &self.points;
+            self.order[range.clone()].select_nth_unstable_by_key(half, |&i| points[i][axis]);
+
+            let left = self.build(range.start..range.start + half);
+            let right = self.build(range.start + half..range.end);
+            self.nodes[index].children = Some([left, right]);
+        }
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, index: usize) -> [i64; D] {
+        self.points[index]
+    }
+
+    /// All points as `(squared distance, index)`, nearest to `query` first. Each step takes
+    /// `O(log n)` amortised for evenly spread points.
+    pub fn nearest(&self, query: impl Coords<D>) -> Nearest<'_, D> {
+        let query = query.coords();
+        let mut queue = BinaryHeap::new();
+        if let Some(root) = self.nodes.first() {
+            queue.push(Reverse((root.distance_to(query), true, 0)));
+        }
+        Nearest {
+            tree: self,
+            query,
+            queue,
+        }
+    }
+
+    /// All pairs of distinct points as `(squared distance, i, j)` with `i < j`, closest first.
+    /// Only does the work for as many pairs as are taken.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, D> {
+        let mut neighbours: Vec<Nearest<'_, D>> = (0..self.len())
+            .map(|i| self.nearest(self.points[i]))
+            .collect();
+        let mut queue = BinaryHeap::new();
+        for (i, nearest) in neighbours.iter_mut().enumerate() {
+            if let Some((dist, j)) = nearest.next_other(i) {
+                queue.push(Reverse((dist, i, j)));
+            }
+        }
+        ClosestPairs { neighbours, queue }
+    }
+}
+
+/// The iterator returned by [`KdTree::nearest`].
+#[derive(Clone, Debug)]
+pub struct Nearest<'a, const D: usize> {
+    tree: &'a KdTree<D>,
+    query: [i64; D],
+    /// Nodes by the distance to their bounding box, and points by their distance, as
+    /// `(distance, is node, index)`. Points go first on ties, since nothing is closer.
+    queue: BinaryHeap<Reverse<(i64, bool, usize)>>,
+}
+
+impl<const D: usize> Nearest<'_, D> {
+    /// The next point other than `index`.
+    fn next_other(&mut self, index: usize) -> Option<(i64, usize)> {
+        self.find(|&(_, i)| i != index)
+    }
+}
+
+impl<const D: usize> Iterator for Nearest<'_, D> {
+    type Item = (i64, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((dist, is_node, index))) = self.queue.pop() {
+            if !is_node {
+                return Some((dist, index));
+            }
+            let node = &self.tree.nodes[index];
+            match node.children {
+                Some(children) => {
+                    for child in children {
+                        let dist = self.tree.nodes[child].distance_to(self.query);
+                        self.queue.push(Reverse((dist, true, child)));
+                    }
+                }
+                None => {
+                    for &i in &self.tree.order[node.range.clone()] {
+                        let dist = squared_distance(self.tree.points[i], self.query);
+                        self.queue.push(Reverse((dist, false, i)));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The iterator returned by [`KdTree::closest_pairs`].
+#[derive(Clone, Debug)]
+pub struct ClosestPairs<'a, const D: usize> {
+    /// The neighbours of every point, nearest first.
+    neighbours: Vec<Nearest<'a, D>>,
+    /// The next neighbour of every point as `(distance, point, neighbour)`. Each pair comes up
+    /// once from either side, and is only reported from the side of its smaller index.
+    queue: BinaryHeap<Reverse<(i64, usize, usize)>>,
+}
+
+impl<const D: usize> Iterator for ClosestPairs<'_, D> {
+    type Item = (i64, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((dist, i, j))) = self.queue.pop() {
+            if let Some((next_dist, next)) = self.neighbours[i].next_other(i) {
+                self.queue.push(Reverse((next_dist, i, next)));
+            }
+            if i < j {
+                return Some((dist, i, j));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KdTree, squared_distance};
+    use crate::geom::Point3;
+
+    /// Deterministic pseudo-random points, with some duplicates.
+    fn points(n: usize) -> Vec<[i64; 3]> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 1000) as i64
+        };
+        let mut points: Vec<[i64; 3]> = (0..n).map(|_| [next(), next(), next()]).collect();
+        points.extend_from_within(..n / 10);
+        points
+    }
+
+    #[test]
+    fn finds_nearest_points_in_order() {
+        let points = points(300);
+        let tree = KdTree::new(points.iter().copied());
+        assert_eq!(tree.len(), points.len());
+
+        for query in [[0, 0, 0], [500, 500, 500], points[42]] {
+            let found: Vec<i64> = tree.nearest(query).map(|(dist, _)| dist).collect();
+            let mut expected: Vec<i64> =
+                points.iter().map(|&p| squared_distance(p, query)).collect();
+            expected.sort_unstable();
+            assert_eq!(found, expected);
+        }
+
+        let (dist, index) = tree.nearest([0, 0, 0]).next().unwrap();
+        assert_eq!(squared_distance(tree.point(index), [0, 0, 0]), dist);
+    }
+
+    #[test]
+    fn streams_closest_pairs() {
+        let points = points(200);
+        let tree = KdTree::new(points.iter().copied());
+
+        let found: Vec<(i64, usize, usize)> = tree.closest_pairs().collect();
+        let mut expected = vec![];
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                expected.push((squared_distance(points[i], points[j]), i, j));
+            }
+        }
+        expected.sort_unstable();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn handles_small_trees() {
+        let tree = KdTree::<3>::new(Vec::<Point3>::new());
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest([1, 2, 3]).next(), None);
+        assert_eq!(tree.closest_pairs().next(), None);
+
+        let tree = KdTree::new([Point3::new(1, 2, 3), Point3::new(1, 2, 5)]);
+        assert_eq!(tree.nearest([1, 2, 4]).count(), 2);
+        assert_eq!(tree.closest_pairs().collect::<Vec<_>>(), vec![(4, 0, 1)]);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::template::{all_days, run_multi::run_multi};
 
-pub fn handle(is_release: bool) {
-    run_multi(&all_days().collect(), is_release, false);
+pub fn handle(is_release: bool, part: Option<u8>) {
+    run_multi(&all_days().collect(), is_release, None, false, part);
 }
@@ -1,14 +1,56 @@
 use std::{
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
     io::Write,
+    path::Path,
     process,
 };
 
-use crate::template::Day;
+use crate::template::{Day, aoc_cli};
 
 const MODULE_TEMPLATE: &str =
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/template.txt"));
 
+/// Built-in scaffold templates, selectable via `--template <name>`.
+const BUILTIN_TEMPLATES: [(&str, &str); 4] = [
+    ("plain", MODULE_TEMPLATE),
+    (
+        "grid",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/grid.txt"
+        )),
+    ),
+    (
+        "graph",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/graph.txt"
+        )),
+    ),
+    (
+        "shared-parse",
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/templates/shared-parse.txt"
+        )),
+    ),
+];
+
+/// Directory for project-local templates. A file `templates/<name>.txt` takes precedence over a built-in template.
+const LOCAL_TEMPLATE_DIR: &str = "templates";
+
+/// Options for the `scaffold` command.
+#[derive(Clone, Debug, Default)]
+pub struct ScaffoldOptions {
+    pub overwrite: bool,
+    /// Name of the template to use, defaults to `plain`.
+    pub template: Option<String>,
+    /// Print what would be created instead of writing files.
+    pub dry_run: bool,
+    /// Expected example answers for part one and two.
+    pub answers: [Option<String>; 2],
+}
+
 fn safe_create_file(path: &str, overwrite: bool) -> Result<File, std::io::Error> {
     let mut file = OpenOptions::new();
     if overwrite {
@@ -27,12 +69,112 @@ fn create_file(path: &str) -> Result<File, std::io::Error> {
         .open(path)
 }
 
-pub fn handle(day: Day, overwrite: bool) {
+fn load_template(name: &str) -> Result<String, String> {
+    let local_path = Path::new(LOCAL_TEMPLATE_DIR).join(format!("{name}.txt"));
+    if local_path.exists() {
+        return fs::read_to_string(&local_path)
+            .map_err(|e| format!("could not read \"{}\": {e}", local_path.display()));
+    }
+
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, contents)| (*contents).to_string())
+        .ok_or_else(|| {
+            let names: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|(name, _)| *name).collect();
+            format!(
+                "unknown template \"{name}\". Use one of {} or add \"{LOCAL_TEMPLATE_DIR}/{name}.txt\"",
+                names.join(", ")
+            )
+        })
+}
+
+/// Read the puzzle title from a downloaded puzzle description, e.g. `Day 1: Secret Entrance`.
+fn read_puzzle_title(day: Day) -> Option<String> {
+    let puzzle = fs::read_to_string(aoc_cli::get_puzzle_path(day)).ok()?;
+    parse_puzzle_title(&puzzle)
+}
+
+fn parse_puzzle_title(puzzle: &str) -> Option<String> {
+    puzzle
+        .lines()
+        .find(|line| line.contains("--- Day "))
+        .map(|line| {
+            line.trim_matches(|c: char| c == '\\' || c == '-' || c.is_whitespace())
+                .to_string()
+        })
+}
+
+/// Replace the placeholders supported in templates:
+///  - `%DAY_NUMBER%`: the day without padding, e.g. `1`.
+///  - `%DAY%`: the zero-padded day, e.g. `01`.
+///  - `%YEAR%`: the value of `AOC_YEAR`.
+///  - `%TITLE%`: the puzzle title if the description was downloaded, `Day <n>` otherwise.
+///  - `%PART_ONE_EXPECTED%` / `%PART_TWO_EXPECTED%`: the example answers as `Some(..)` or `None`.
+fn render_template(
+    template: &str,
+    day: Day,
+    title: Option<&str>,
+    answers: &[Option<String>; 2],
+) -> String {
+    let expected = |answer: &Option<String>| {
+        answer
+            .as_ref()
+            .map_or_else(|| "None".to_string(), |x| format!("Some({x})"))
+    };
+
+    template
+        .replace("%DAY_NUMBER%", &day.into_inner().to_string())
+        .replace("%DAY%", &day.to_string())
+        .replace(
+            "%YEAR%",
+            &aoc_cli::get_year().map_or_else(String::new, |x| x.to_string()),
+        )
+        .replace(
+            "%TITLE%",
+            title.unwrap_or(&format!("Day {}", day.into_inner())),
+        )
+        .replace("%PART_ONE_EXPECTED%", &expected(&answers[0]))
+        .replace("%PART_TWO_EXPECTED%", &expected(&answers[1]))
+}
+
+pub fn handle(day: Day, options: &ScaffoldOptions) {
     let input_path = format!("data/inputs/{day}.txt");
     let example_path = format!("data/examples/{day}.txt");
     let module_path = format!("src/bin/{day}.rs");
 
-    let mut file = match safe_create_file(&module_path, overwrite) {
+    let template = match load_template(options.template.as_deref().unwrap_or("plain")) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("Failed to load template: {e}.");
+            process::exit(1);
+        }
+    };
+
+    let contents = render_template(
+        &template,
+        day,
+        read_puzzle_title(day).as_deref(),
+        &options.answers,
+    );
+
+    if options.dry_run {
+        if Path::new(&module_path).exists() && !options.overwrite {
+            println!(
+                "Module file \"{module_path}\" already exists, pass `--overwrite` to replace it."
+            );
+        } else {
+            println!("Would create module file \"{module_path}\":");
+            println!("---");
+            print!("{contents}");
+            println!("---");
+        }
+        println!("Would create empty input file \"{input_path}\"");
+        println!("Would create empty example file \"{example_path}\"");
+        return;
+    }
+
+    let mut file = match safe_create_file(&module_path, options.overwrite) {
         Ok(file) => file,
         Err(e) => {
             eprintln!("Failed to create module file: {e}");
@@ -40,11 +182,7 @@ pub fn handle(day: Day, overwrite: bool) {
         }
     };
 
-    match file.write_all(
-        MODULE_TEMPLATE
-            .replace("%DAY_NUMBER%", &day.into_inner().to_string())
-            .as_bytes(),
-    ) {
+    match file.write_all(contents.as_bytes()) {
         Ok(()) => {
             println!("Created module file \"{}\"", &module_path);
         }
@@ -77,3 +215,47 @@ pub fn handle(day: Day, overwrite: bool) {
     println!("---");
     println!("🎄 Type `cargo solve {day}` to run your solution.");
 }
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{BUILTIN_TEMPLATES, parse_puzzle_title, render_template};
+    use crate::day;
+
+    #[test]
+    fn renders_placeholders() {
+        let rendered = render_template(
+            "%DAY_NUMBER% %DAY% %TITLE% %PART_ONE_EXPECTED% %PART_TWO_EXPECTED%",
+            day!(7),
+            Some("Day 7: Laboratories"),
+            &[Some("21".into()), None],
+        );
+        assert_eq!(rendered, "7 07 Day 7: Laboratories Some(21) None");
+    }
+
+    #[test]
+    fn falls_back_to_day_title() {
+        let rendered = render_template("%TITLE%", day!(3), None, &[None, None]);
+        assert_eq!(rendered, "Day 3");
+    }
+
+    #[test]
+    fn parses_puzzle_titles() {
+        let puzzle = "\\--- Day 1: Secret Entrance ---\n----------\n\nThe Elves have good news";
+        assert_eq!(
+            parse_puzzle_title(puzzle),
+            Some("Day 1: Secret Entrance".into())
+        );
+        assert_eq!(parse_puzzle_title("no title"), None);
+    }
+
+    #[test]
+    fn builtin_templates_have_no_leftover_placeholders() {
+        for (name, template) in BUILTIN_TEMPLATES {
+            let rendered = render_template(template, day!(1), None, &[None, None]);
+            assert!(
+                !rendered.contains('%'),
+                "template {name} has leftover placeholders"
+            );
+        }
+    }
+}
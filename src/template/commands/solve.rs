@@ -1,28 +1,75 @@
 use std::process::{Command, Stdio};
 
-use crate::template::Day;
+use crate::template::{
+    Day,
+    runner::{BenchOptions, VizOptions},
+    solution_args::DayArgs,
+};
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// Options for the `solve` command.
+#[derive(Clone, Debug, Default)]
+pub struct SolveOptions {
+    pub release: bool,
+    pub dhat: bool,
+    pub perf: bool,
+    pub bench: Option<BenchOptions>,
+    pub part: Option<u8>,
+    pub submit: Option<u8>,
+    /// Where frames go, if the solution is visualised.
+    pub viz: Option<VizOptions>,
+    /// Options for the solution of the day, passed on after `--`.
+    pub day_args: DayArgs,
+}
+
+pub fn handle(day: Day, options: &SolveOptions) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
+    let mut features = vec![];
 
-    if dhat {
-        cmd_args.extend([
-            "--profile".to_string(),
-            "dhat".to_string(),
-            "--features".to_string(),
-            "dhat-heap".to_string(),
-        ]);
-    } else if release {
+    if options.dhat {
+        cmd_args.extend(["--profile".to_string(), "dhat".to_string()]);
+        features.push("dhat-heap");
+    } else if options.release {
         cmd_args.push("--release".to_string());
     }
 
+    if options.viz.is_some() {
+        features.push("viz");
+    }
+
+    if !features.is_empty() {
+        cmd_args.extend(["--features".to_string(), features.join(",")]);
+    }
+
     cmd_args.push("--".to_string());
 
-    if let Some(submit_part) = submit_part {
+    if let Some(viz_options) = &options.viz {
+        cmd_args.extend(viz_options.to_args());
+    }
+
+    if options.perf {
+        cmd_args.push("--perf".to_string());
+    }
+
+    if let Some(bench_options) = &options.bench {
+        cmd_args.push("--time".to_string());
+        cmd_args.extend(bench_options.to_args());
+    }
+
+    if let Some(part) = options.part {
+        cmd_args.push("--part".to_string());
+        cmd_args.push(part.to_string());
+    }
+
+    if let Some(submit_part) = options.submit {
         cmd_args.push("--submit".to_string());
         cmd_args.push(submit_part.to_string());
     }
 
+    if !options.day_args.as_slice().is_empty() {
+        cmd_args.push("--".to_string());
+        cmd_args.extend(options.day_args.as_slice().iter().cloned());
+    }
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
@@ -1,10 +1,18 @@
 use std::collections::HashSet;
 
 use crate::template::run_multi::run_multi;
+use crate::template::runner::BenchOptions;
 use crate::template::timings::Timings;
 use crate::template::{Day, all_days, readme_benchmarks};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+pub fn handle(
+    day: Option<Day>,
+    run_all: bool,
+    store: bool,
+    perf: bool,
+    bench_options: &BenchOptions,
+    part: Option<u8>,
+) {
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -14,14 +22,17 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
             } else {
                 // when the `--all` flag is not set, filter out days that are fully benched.
                 all_days()
-                    .filter(|day| !stored_timings.is_day_complete(*day))
+                    .filter(|day| match part {
+                        Some(part) => !stored_timings.is_part_complete(*day, part),
+                        None => !stored_timings.is_day_complete(*day),
+                    })
                     .collect()
             }
         },
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let timings = run_multi(&days_to_run, true, Some(bench_options), perf, part).unwrap();
 
     if store {
         let merged_timings = stored_timings.merge(&timings);
@@ -2,9 +2,12 @@ use std::{env, fs};
 
 pub mod aoc_cli;
 pub mod commands;
+pub mod perf;
 pub mod runner;
+pub mod solution_args;
 
 pub use day::*;
+pub use solution_args::{DayOption, day_args};
 
 mod day;
 mod readme_benchmarks;
@@ -39,19 +42,30 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Days with day options declare them instead, as `options: &[DayOption]`, optionally followed by
+/// `setup: fn() -> Result<(), String>` and `report: fn(&str, u8)`. The setup runs once before
+/// any part, to load what the options refer to, and its error ends the program. The report runs
+/// once after each part, outside the timed runs, for options that print details about the
+/// solution.
+/// The generated `main` parses the command-line arguments once, see `--help` for a list of them.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, &[], [], [], [part_one, 1] [part_two, 2]);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, &[], [], [], [part_one, 1]);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, &[], [], [], [part_two, 2]);
+    };
+    ($day:expr, options: $options:expr $(, setup: $setup:expr)? $(, report: $report:expr)?) => {
+        $crate::solution!(
+            @impl $day, $options, [$($setup)?], [$($report)?], [part_one, 1] [part_two, 2]
+        );
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $options:expr, [$($setup:expr)?], [$($report:expr)?], $( [$func:expr, $part:expr] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -61,8 +75,18 @@ macro_rules! solution {
 
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let args = SolutionArgs::from_env($options);
+            $( args.setup($setup); )?
+            let reports: &[fn(&str, u8)] = &[$($report)?];
+            let input = args.read_input(DAY);
+            $(
+                if args.runs_part($part) {
+                    run_part($func, &input, DAY, $part, &args);
+                    for report in reports {
+                        report(&input, $part);
+                    }
+                }
+            )*
         }
     };
 }
@@ -0,0 +1,357 @@
+/// Reads hardware performance counters around solution runs.
+/// On Linux this uses the `perf_event_open` syscall, other platforms always report the counters as unavailable.
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use tinyjson::JsonValue;
+
+/// Averaged hardware counter values for one part of a solution.
+/// Counters that the kernel or the CPU does not expose are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PerfStats {
+    pub cycles: Option<f64>,
+    pub instructions: Option<f64>,
+    pub branch_misses: Option<f64>,
+    pub cache_misses: Option<f64>,
+    pub samples: u128,
+}
+
+impl PerfStats {
+    /// Instructions retired per cycle, if both counters are available.
+    pub fn ipc(&self) -> Option<f64> {
+        match (self.instructions, self.cycles) {
+            (Some(instructions), Some(cycles)) if cycles > 0.0 => Some(instructions / cycles),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PerfStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_counter = |value: Option<f64>| value.map_or_else(|| "-".into(), format_count);
+
+        write!(
+            f,
+            "cycles: {}, instructions: {}",
+            fmt_counter(self.cycles),
+            fmt_counter(self.instructions)
+        )?;
+
+        if let Some(ipc) = self.ipc() {
+            write!(f, " (IPC {ipc:.2})")?;
+        }
+
+        write!(
+            f,
+            ", branch misses: {}, cache misses: {} @ {} samples",
+            fmt_counter(self.branch_misses),
+            fmt_counter(self.cache_misses),
+            self.samples
+        )
+    }
+}
+
+impl From<&PerfStats> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &PerfStats) -> Self {
+        let to_json = |x: Option<f64>| x.map_or(JsonValue::Null, JsonValue::Number);
+
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert("cycles".into(), to_json(value.cycles));
+        map.insert("instructions".into(), to_json(value.instructions));
+        map.insert("branch_misses".into(), to_json(value.branch_misses));
+        map.insert("cache_misses".into(), to_json(value.cache_misses));
+        map.insert("samples".into(), JsonValue::Number(value.samples as f64));
+        JsonValue::Object(map)
+    }
+}
+
+/// An error which can be returned when the performance counters cannot be opened.
+#[derive(Debug)]
+pub enum PerfError {
+    Unsupported,
+    AccessDenied,
+    IO(std::io::Error),
+}
+
+impl Display for PerfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PerfError::Unsupported => {
+                write!(f, "hardware counters are not supported on this platform.")
+            }
+            PerfError::AccessDenied => write!(
+                f,
+                "access to perf events was denied. Try lowering /proc/sys/kernel/perf_event_paranoid."
+            ),
+            PerfError::IO(e) => write!(f, "could not open perf events: {e}"),
+        }
+    }
+}
+
+fn format_count(value: f64) -> String {
+    match value {
+        v if v >= 1e9 => format!("{:.2}G", v / 1e9),
+        v if v >= 1e6 => format!("{:.2}M", v / 1e6),
+        v if v >= 1e3 => format!("{:.2}k", v / 1e3),
+        v => format!("{v:.0}"),
+    }
+}
+
+/// Runs `func` `samples` times and averages the hardware counters across all runs.
+pub fn measure<I: Copy, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    samples: u128,
+) -> Result<PerfStats, PerfError> {
+    let counters = sys::Counters::open()?;
+    let samples = samples.max(1);
+
+    let mut totals = [0_u64; sys::EVENT_COUNT];
+
+    for _ in 0..samples {
+        counters.start();
+        std::hint::black_box(func(std::hint::black_box(input)));
+        counters.stop();
+
+        for (total, value) in totals.iter_mut().zip(counters.read()) {
+            *total += value.unwrap_or(0);
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let average = |index: usize| {
+        counters
+            .is_open(index)
+            .then(|| totals[index] as f64 / samples as f64)
+    };
+
+    Ok(PerfStats {
+        cycles: average(0),
+        instructions: average(1),
+        branch_misses: average(2),
+        cache_misses: average(3),
+        samples,
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::PerfError;
+    use std::io;
+
+    pub const EVENT_COUNT: usize = 4;
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+
+    // bits of the `flags` bitfield in `perf_event_attr`.
+    const FLAG_DISABLED: u64 = 1 << 0;
+    const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+    /// Mirror of the kernel's `perf_event_attr` struct (`PERF_ATTR_SIZE_VER5`).
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved_2: u16,
+    }
+
+    /// A set of per-thread hardware counters.
+    /// Events that fail to open individually (e.g. inside VMs) are skipped.
+    pub struct Counters {
+        fds: [Option<libc::c_int>; EVENT_COUNT],
+    }
+
+    impl Counters {
+        pub fn open() -> Result<Self, PerfError> {
+            let configs = [
+                PERF_COUNT_HW_CPU_CYCLES,
+                PERF_COUNT_HW_INSTRUCTIONS,
+                PERF_COUNT_HW_BRANCH_MISSES,
+                PERF_COUNT_HW_CACHE_MISSES,
+            ];
+
+            let mut fds = [None; EVENT_COUNT];
+            let mut last_error = None;
+
+            for (fd, config) in fds.iter_mut().zip(configs) {
+                match open_event(config) {
+                    Ok(x) => *fd = Some(x),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+
+            if fds.iter().all(Option::is_none) {
+                return Err(match last_error {
+                    Some(e) if e.raw_os_error() == Some(libc::EACCES) => PerfError::AccessDenied,
+                    Some(e) if e.raw_os_error() == Some(libc::EPERM) => PerfError::AccessDenied,
+                    Some(e) if e.raw_os_error() == Some(libc::ENOENT) => PerfError::Unsupported,
+                    Some(e) if e.raw_os_error() == Some(libc::ENOSYS) => PerfError::Unsupported,
+                    Some(e) => PerfError::IO(e),
+                    None => PerfError::Unsupported,
+                });
+            }
+
+            Ok(Self { fds })
+        }
+
+        pub fn is_open(&self, index: usize) -> bool {
+            self.fds[index].is_some()
+        }
+
+        pub fn start(&self) {
+            for fd in self.fds.iter().flatten() {
+                unsafe {
+                    libc::ioctl(*fd, PERF_EVENT_IOC_RESET, 0);
+                    libc::ioctl(*fd, PERF_EVENT_IOC_ENABLE, 0);
+                }
+            }
+        }
+
+        pub fn stop(&self) {
+            for fd in self.fds.iter().flatten() {
+                unsafe {
+                    libc::ioctl(*fd, PERF_EVENT_IOC_DISABLE, 0);
+                }
+            }
+        }
+
+        pub fn read(&self) -> [Option<u64>; EVENT_COUNT] {
+            self.fds.map(|fd| {
+                let fd = fd?;
+                let mut value = 0_u64;
+                let read = unsafe {
+                    libc::read(
+                        fd,
+                        (&raw mut value).cast::<libc::c_void>(),
+                        size_of::<u64>(),
+                    )
+                };
+                (read == size_of::<u64>().cast_signed()).then_some(value)
+            })
+        }
+    }
+
+    impl Drop for Counters {
+        fn drop(&mut self) {
+            for fd in self.fds.iter().flatten() {
+                unsafe {
+                    libc::close(*fd);
+                }
+            }
+        }
+    }
+
+    fn open_event(config: u64) -> io::Result<libc::c_int> {
+        let attr = PerfEventAttr {
+            type_: PERF_TYPE_HARDWARE,
+            #[allow(clippy::cast_possible_truncation)]
+            size: size_of::<PerfEventAttr>() as u32,
+            config,
+            flags: FLAG_DISABLED | FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+            ..Default::default()
+        };
+
+        // measure the calling thread on any cpu.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &raw const attr,
+                0 as libc::pid_t,
+                -1 as libc::c_int,
+                -1 as libc::c_int,
+                0 as libc::c_ulong,
+            )
+        };
+
+        if fd < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            #[allow(clippy::cast_possible_truncation)]
+            Ok(fd as libc::c_int)
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::PerfError;
+
+    pub const EVENT_COUNT: usize = 4;
+
+    pub struct Counters;
+
+    impl Counters {
+        pub fn open() -> Result<Self, PerfError> {
+            Err(PerfError::Unsupported)
+        }
+
+        pub fn is_open(&self, _index: usize) -> bool {
+            false
+        }
+
+        pub fn start(&self) {}
+
+        pub fn stop(&self) {}
+
+        pub fn read(&self) -> [Option<u64>; EVENT_COUNT] {
+            [None; EVENT_COUNT]
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::PerfStats;
+
+    #[test]
+    fn formats_available_counters() {
+        let stats = PerfStats {
+            cycles: Some(2_000_000.0),
+            instructions: Some(5_000_000.0),
+            branch_misses: Some(1_500.0),
+            cache_misses: None,
+            samples: 10,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "cycles: 2.00M, instructions: 5.00M (IPC 2.50), branch misses: 1.50k, cache misses: - @ 10 samples"
+        );
+    }
+
+    #[test]
+    fn skips_ipc_without_cycles() {
+        let stats = PerfStats {
+            instructions: Some(100.0),
+            samples: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.ipc(), None);
+    }
+}
@@ -1,48 +1,290 @@
 /// Encapsulates code that interacts with solution functions.
+use std::cmp;
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{Write, stdout};
+use std::path::PathBuf;
 use std::process::Output;
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+
+use tinyjson::JsonValue;
+
+pub use crate::template::solution_args::{OutputFormat, SolutionArgs};
 
 use crate::template::ANSI_BOLD;
-use crate::template::{ANSI_ITALIC, ANSI_RESET, Day, aoc_cli};
+use crate::template::perf::{PerfError, PerfStats};
+use crate::template::{ANSI_ITALIC, ANSI_RESET, Day, aoc_cli, perf};
+use crate::viz;
+
+/// Controls how long and how often a solution part is executed when benching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BenchOptions {
+    /// Approximate execution time to spend on the samples of a single part.
+    pub budget: Duration,
+    pub min_samples: u128,
+    pub max_samples: u128,
+    /// Number of untimed runs before samples are taken.
+    pub warmup: u128,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            budget: Duration::from_secs(1),
+            min_samples: 10,
+            max_samples: 10000,
+            warmup: 0,
+        }
+    }
+}
+
+impl BenchOptions {
+    /// Parse `--budget`, `--min-samples`, `--max-samples` and `--warmup`, falling back to defaults.
+    pub fn from_args(args: &mut pico_args::Arguments) -> Result<Self, pico_args::Error> {
+        let defaults = Self::default();
+
+        let options = Self {
+            budget: args
+                .opt_value_from_fn("--budget", parse_budget)?
+                .unwrap_or(defaults.budget),
+            min_samples: args
+                .opt_value_from_str("--min-samples")?
+                .unwrap_or(defaults.min_samples),
+            max_samples: args
+                .opt_value_from_str("--max-samples")?
+                .unwrap_or(defaults.max_samples),
+            warmup: args
+                .opt_value_from_str("--warmup")?
+                .unwrap_or(defaults.warmup),
+        };
+
+        if options.min_samples == 0 || options.min_samples > options.max_samples {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!(
+                    "expected 0 < --min-samples <= --max-samples, got {} and {}",
+                    options.min_samples, options.max_samples
+                ),
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Serialize the options back to command-line arguments for a child process.
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            "--budget".into(),
+            format!("{}ns", self.budget.as_nanos()),
+            "--min-samples".into(),
+            self.min_samples.to_string(),
+            "--max-samples".into(),
+            self.max_samples.to_string(),
+            "--warmup".into(),
+            self.warmup.to_string(),
+        ]
+    }
 
-pub fn run_part<I: Copy, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+    /// Number of samples to take for a function that took `base_time` on its first run.
+    pub fn iterations(&self, base_time: &Duration) -> u128 {
+        (self.budget.as_nanos() / cmp::max(base_time.as_nanos(), 10))
+            .clamp(self.min_samples, self.max_samples)
+    }
+}
+
+/// Where the frames of a solution go, see `--viz` and `--viz-out`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VizOptions {
+    /// Animate the frames in the terminal.
+    pub terminal: bool,
+    /// Export the frames to this file, in the format of its extension.
+    pub out: Option<PathBuf>,
+    /// The time each frame is shown for.
+    pub delay: Duration,
+}
+
+/// The time each frame is shown for by default.
+pub const DEFAULT_VIZ_DELAY: Duration = Duration::from_millis(50);
+
+impl VizOptions {
+    /// Parse `--viz`, `--viz-out` and `--viz-delay`, or `None` if frames go nowhere.
+    pub fn from_args(args: &mut pico_args::Arguments) -> Result<Option<Self>, pico_args::Error> {
+        let terminal = args.contains("--viz");
+        let out: Option<PathBuf> =
+            args.opt_value_from_os_str("--viz-out", |s| Ok::<_, String>(PathBuf::from(s)))?;
+        let delay = args.opt_value_from_fn("--viz-delay", parse_budget)?;
+
+        if let Some(path) = &out {
+            viz::export::Format::from_path(path)
+                .map_err(|cause| pico_args::Error::ArgumentParsingFailed { cause })?;
+        }
+
+        if !terminal && out.is_none() {
+            return match delay {
+                Some(_) => Err(pico_args::Error::ArgumentParsingFailed {
+                    cause: "`--viz-delay` requires `--viz` or `--viz-out`".into(),
+                }),
+                None => Ok(None),
+            };
+        }
+
+        Ok(Some(Self {
+            terminal,
+            out,
+            delay: delay.unwrap_or(DEFAULT_VIZ_DELAY),
+        }))
+    }
+
+    /// Serialize the options back to command-line arguments for a child process.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.terminal {
+            args.push("--viz".into());
+        }
+        if let Some(out) = &self.out {
+            args.extend(["--viz-out".into(), out.display().to_string()]);
+        }
+        args.extend(["--viz-delay".into(), format!("{}ns", self.delay.as_nanos())]);
+        args
+    }
+
+    /// The viewer for a run of `part`. When both parts run, each exports to its own file, e.g.
+    /// `out-part1.svg`.
+    pub fn viewer(&self, part: u8, both_parts: bool) -> Box<dyn viz::Viewer> {
+        let mut viewers: Vec<Box<dyn viz::Viewer>> = vec![];
+        if self.terminal {
+            viewers.push(Box::new(viz::Terminal::new(self.delay)));
+        }
+        if let Some(out) = &self.out {
+            let path = if both_parts {
+                let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+                let ext = out.extension().unwrap_or_default().to_string_lossy();
+                out.with_file_name(format!("{stem}-part{part}.{ext}"))
+            } else {
+                out.clone()
+            };
+            // The format was checked when parsing the options.
+            viewers.push(viz::export::exporter(path, self.delay).unwrap());
+        }
+        Box::new(viewers)
+    }
+}
+
+/// Parse a duration like `500ms`, `1.5s`, `250us` or `100000ns`. Values without a unit are seconds.
+pub fn parse_budget(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration \"{s}\""))?;
+
+    let secs = match unit {
+        "ns" => value / 1e9,
+        "us" | "µs" => value / 1e6,
+        "ms" => value / 1e3,
+        "s" | "" => value,
+        _ => return Err(format!("unknown duration unit \"{unit}\"")),
+    };
+
+    Duration::try_from_secs_f64(secs).map_err(|e| e.to_string())
+}
+
+pub fn run_part<I: Copy, T: Display>(
+    func: impl Fn(I) -> Option<T>,
+    input: I,
+    day: Day,
+    part: u8,
+    args: &SolutionArgs,
+) {
     let part_str = format!("Part {part}");
+    let interactive = args.is_interactive();
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let viewer = args
+        .viz
+        .as_ref()
+        .map(|viz| viz.viewer(part, args.part.is_none()));
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    let (result, duration, samples) = run_timed(&func, input, args, viewer, |result| {
+        if interactive {
+            print_result(result, &part_str, "");
+        }
+    });
+
+    let perf = args
+        .perf
+        .then(|| measure_perf(&func, input, &duration, args));
 
-    if let Some(result) = result {
-        submit_result(result, day, part);
+    match args.format {
+        OutputFormat::Text if args.quiet => {
+            if let Some(result) = &result {
+                println!("{result}");
+            }
+        }
+        OutputFormat::Text => {
+            print_result(&result, &part_str, &format_duration(&duration, samples));
+
+            match &perf {
+                Some(Ok(stats)) => println!(" > {ANSI_ITALIC}perf:{ANSI_RESET} {stats}"),
+                Some(Err(e)) => println!(" > {ANSI_ITALIC}perf:{ANSI_RESET} unavailable, {e}"),
+                None => {}
+            }
+        }
+        OutputFormat::Json => {
+            let json = result_to_json(day, part, result.as_ref(), &duration, samples, perf);
+            println!("{}", json.stringify().unwrap_or_default());
+        }
+    }
+
+    if args.submit == Some(part)
+        && let Some(result) = result
+        && let Err(e) = submit_result(result, day, part)
+    {
+        eprintln!("Failed to submit result: {e}");
     }
 }
 
-/// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
-///  1. in debug, the function is executed once.
-///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+/// Run a solution part. The behavior differs depending on whether the `--time` flag is passed:
+///  1. without it, the function is executed once, or twice if visualised.
+///  2. with it, the function is benched (approx. `budget` of execution time or `min_samples` samples, whatever take longer.)
 fn run_timed<I: Copy, T>(
     func: impl Fn(I) -> T,
     input: I,
+    args: &SolutionArgs,
+    viewer: Option<Box<dyn viz::Viewer>>,
     hook: impl Fn(&T),
 ) -> (T, Duration, u128) {
+    let visualised = viewer.is_some();
     let timer = Instant::now();
     let result = {
         #[cfg(feature = "dhat-heap")]
         let _profiler = dhat::Profiler::new_heap();
 
-        func(input)
+        // Only the first run is visualised, bench samples stay free of frames.
+        if let Some(viewer) = viewer {
+            viz::attach(viewer);
+        }
+        let result = func(input);
+        viz::detach();
+        result
     };
-    let base_time = timer.elapsed();
+    let mut base_time = timer.elapsed();
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    // Building frames, exporting them and the frame delays are no part of the solution, so a
+    // visualised run is timed again without the viewer.
+    if visualised {
+        let timer = Instant::now();
+        black_box(func(black_box(input)));
+        base_time = timer.elapsed();
+    }
+
+    let run = if args.time {
+        bench(func, input, &base_time, args)
     } else {
         (base_time, 1)
     };
@@ -50,14 +292,22 @@ fn run_timed<I: Copy, T>(
     (result, run.0, run.1)
 }
 
-fn bench<I: Copy, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
-    let mut stdout = stdout();
+fn bench<I: Copy, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    args: &SolutionArgs,
+) -> (Duration, u128) {
+    if args.is_interactive() {
+        print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
+        let _ = stdout().flush();
+    }
 
-    print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+    for _ in 0..args.bench.warmup {
+        black_box(func(black_box(input)));
+    }
 
-    let bench_iterations =
-        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
+    let bench_iterations = args.bench.iterations(base_time);
 
     let mut timers: Vec<Duration> = vec![];
 
@@ -74,6 +324,28 @@ fn bench<I: Copy, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (
     )
 }
 
+/// Sample hardware counters for a solution part.
+/// Uses the same amount of iterations as a benchmark run would.
+fn measure_perf<I: Copy, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    args: &SolutionArgs,
+) -> Result<PerfStats, PerfError> {
+    if args.is_interactive() {
+        print!(" > {ANSI_ITALIC}reading perf counters{ANSI_RESET}");
+        let _ = stdout().flush();
+    }
+
+    let result = perf::measure(func, input, args.bench.iterations(base_time));
+
+    if args.is_interactive() {
+        print!("\r");
+    }
+
+    result
+}
+
 fn average_duration(numbers: &[Duration]) -> u128 {
     numbers
         .iter()
@@ -125,43 +397,116 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
     }
 }
 
-/// Parse the arguments passed to `solve` and try to submit one part of the solution if:
-///  1. we are in `--release` mode.
-///  2. aoc-cli is installed.
+#[allow(clippy::cast_precision_loss)]
+fn result_to_json<T: Display>(
+    day: Day,
+    part: u8,
+    result: Option<&T>,
+    duration: &Duration,
+    samples: u128,
+    perf: Option<Result<PerfStats, PerfError>>,
+) -> JsonValue {
+    let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+    map.insert("day".into(), JsonValue::String(day.to_string()));
+    map.insert("part".into(), JsonValue::Number(f64::from(part)));
+    map.insert(
+        "result".into(),
+        result.map_or(JsonValue::Null, |x| JsonValue::String(x.to_string())),
+    );
+    map.insert(
+        "nanos".into(),
+        JsonValue::Number(duration.as_nanos() as f64),
+    );
+    map.insert("samples".into(), JsonValue::Number(samples as f64));
+
+    if let Some(perf) = perf {
+        map.insert(
+            "perf".into(),
+            match perf {
+                Ok(stats) => JsonValue::from(&stats),
+                Err(e) => JsonValue::String(e.to_string()),
+            },
+        );
+    }
+
+    JsonValue::Object(map)
+}
+
+/// Try to submit one part of the solution via aoc-cli.
 fn submit_result<T: Display>(
     result: T,
     day: Day,
     part: u8,
-) -> Option<Result<Output, aoc_cli::AocCommandError>> {
-    let args: Vec<String> = env::args().collect();
+) -> Result<Output, aoc_cli::AocCommandError> {
+    if let Err(e) = aoc_cli::check() {
+        eprintln!(
+            "command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it."
+        );
+        return Err(e);
+    }
+
+    println!("Submitting result via aoc-cli...");
+    aoc_cli::submit(day, part, &result.to_string())
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{BenchOptions, parse_budget};
+    use std::ffi::OsString;
+    use std::time::Duration;
 
-    if !args.contains(&"--submit".into()) {
-        return None;
+    fn parse(args: &[&str]) -> Result<BenchOptions, pico_args::Error> {
+        let mut args = pico_args::Arguments::from_vec(args.iter().map(OsString::from).collect());
+        BenchOptions::from_args(&mut args)
     }
 
-    if args.len() < 3 {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
+    #[test]
+    fn parses_budget_units() {
+        assert_eq!(parse_budget("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_budget("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_budget("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_budget("40us"), Ok(Duration::from_micros(40)));
+        assert_eq!(parse_budget("40µs"), Ok(Duration::from_micros(40)));
+        assert_eq!(parse_budget("100ns"), Ok(Duration::from_nanos(100)));
+        assert!(parse_budget("10m").is_err());
+        assert!(parse_budget("fast").is_err());
     }
 
-    let part_index = args.iter().position(|x| x == "--submit").unwrap() + 1;
+    #[test]
+    fn uses_defaults_without_arguments() {
+        assert_eq!(parse(&[]).unwrap(), BenchOptions::default());
+    }
 
-    let Ok(part_submit) = args[part_index].parse::<u8>() else {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
-    };
+    #[test]
+    fn round_trips_through_arguments() {
+        let options =
+            parse(&["--budget", "300ms", "--max-samples", "50", "--warmup", "3"]).unwrap();
+        assert_eq!(options.budget, Duration::from_millis(300));
+        assert_eq!(options.min_samples, 10);
+        assert_eq!(options.max_samples, 50);
+        assert_eq!(options.warmup, 3);
 
-    if part_submit != part {
-        return None;
+        let args = options.to_args();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        assert_eq!(parse(&args).unwrap(), options);
     }
 
-    if aoc_cli::check().is_err() {
-        eprintln!(
-            "command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it."
-        );
-        process::exit(1);
+    #[test]
+    fn rejects_inverted_sample_bounds() {
+        assert!(parse(&["--min-samples", "100", "--max-samples", "10"]).is_err());
     }
 
-    println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    #[test]
+    fn clamps_iterations() {
+        let options = BenchOptions {
+            budget: Duration::from_millis(10),
+            min_samples: 5,
+            max_samples: 100,
+            warmup: 0,
+        };
+        assert_eq!(options.iterations(&Duration::from_millis(1)), 10);
+        assert_eq!(options.iterations(&Duration::from_secs(1)), 5);
+        assert_eq!(options.iterations(&Duration::from_nanos(1)), 100);
+    }
 }
@@ -0,0 +1,485 @@
+/// Command-line arguments understood by the solution binaries generated with `solution!`.
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::{fmt::Display, fs, process};
+
+use crate::template::{
+    Day, read_file,
+    runner::{BenchOptions, VizOptions},
+};
+
+const HELP: &str = "\
+Runs the solution for a single day of advent.
+
+USAGE:
+  cargo solve <day> [OPTIONS] [-- <DAY OPTIONS>]
+  cargo run --bin <day> -- [OPTIONS] [-- <DAY OPTIONS>]
+
+OPTIONS:
+  --part <1|2>              Only run the given part of the solution.
+  --input <path>            Read the puzzle input from <path> instead of data/inputs.
+  --format <text|json>      Print results as text (default) or as one JSON object per part.
+  --quiet                   Only print the results.
+  --time                    Bench the solution.
+  --budget <duration>       Approximate execution time per part when benching [default: 1s].
+  --min-samples <n>         Lower bound for the number of bench samples [default: 10].
+  --max-samples <n>         Upper bound for the number of bench samples [default: 10000].
+  --warmup <n>              Untimed runs before bench samples are taken [default: 0].
+  --perf                    Read hardware counters around each run (Linux only).
+  --submit <1|2>            Submit the result of the given part via aoc-cli.
+  --viz                     Animate the solution's frames in the terminal (needs the `viz` feature).
+  --viz-out <path>          Export the frames to a .svg, .ppm, .png or .cast file (needs the `viz`
+                            feature). Both parts export to <name>-part1 and <name>-part2.
+  --viz-delay <duration>    Time each frame is shown for [default: 50ms].
+  -h, --help                Print this help.
+
+DAY OPTIONS:
+  Everything after `--` is left to the solution of the day, see its source for what they do.
+";
+
+/// The format results of a solution binary are printed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("expected `text` or `json`, got `{s}`")),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => f.write_str("text"),
+            Self::Json => f.write_str("json"),
+        }
+    }
+}
+
+/// Options a solution binary was invoked with. Parsed once in `main` and passed to each part.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolutionArgs {
+    pub part: Option<u8>,
+    pub input: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub quiet: bool,
+    pub time: bool,
+    pub bench: BenchOptions,
+    pub perf: bool,
+    pub submit: Option<u8>,
+    /// Where frames go, if they are shown with `--viz` or exported with `--viz-out`.
+    pub viz: Option<VizOptions>,
+    /// Options for the solution of the day, passed after `--`.
+    pub day_args: DayArgs,
+}
+
+/// Options that only the solution of one day understands, passed after `--`, e.g.
+/// `cargo solve 12 -- --layouts`. Solutions declare them as [`DayOption`]s and read them with
+/// [`day_args`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DayArgs(Vec<String>);
+
+impl DayArgs {
+    pub fn new(args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self(args.into_iter().map(Into::into).collect())
+    }
+
+    pub fn as_slice(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Check the arguments against the `options` a day accepts: unknown options, missing values
+    /// and values that don't parse are errors.
+    pub fn validate(&self, options: &[DayOption]) -> Result<(), String> {
+        let mut args = self.0.iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = options.iter().find(|option| option.name == arg) else {
+                return Err(match options {
+                    [] => format!("unknown day option `{arg}`, this day takes none"),
+                    _ => format!(
+                        "unknown day option `{arg}`, expected one of {}",
+                        options
+                            .iter()
+                            .map(|option| format!("`{}`", option.name))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                });
+            };
+            if let Some(check) = option.check {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("the day option `{arg}` needs a value"))?;
+                check(value).map_err(|e| {
+                    format!("invalid value `{value}` for the day option `{arg}`: {e}")
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the flag `name` was passed.
+    pub fn flag(&self, name: &str) -> bool {
+        self.0.iter().any(|arg| arg == name)
+    }
+
+    /// The value of the last `name <value>`, if the option was passed.
+    ///
+    /// # Panics
+    ///
+    /// If the value is missing or malformed, which [`DayArgs::validate`] rules out.
+    pub fn value<T: FromStr<Err: Display>>(&self, name: &str) -> Option<T> {
+        self.values(name).pop()
+    }
+
+    /// The values of all occurrences of `name <value>`, for options that can be repeated.
+    ///
+    /// # Panics
+    ///
+    /// If a value is missing or malformed, which [`DayArgs::validate`] rules out.
+    pub fn values<T: FromStr<Err: Display>>(&self, name: &str) -> Vec<T> {
+        let mut values = vec![];
+        let mut args = self.0.iter();
+        while let Some(arg) = args.next() {
+            if arg != name {
+                continue;
+            }
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("the day option `{name}` needs a value"));
+            values.push(value.parse().unwrap_or_else(|e| {
+                panic!("invalid value `{value}` for the day option `{name}`: {e}")
+            }));
+        }
+        values
+    }
+}
+
+/// A day option that a solution accepts, declared with `solution!` so that the day options can
+/// be checked before the solution runs.
+#[derive(Clone, Copy, Debug)]
+pub struct DayOption {
+    name: &'static str,
+    /// Checks the value of options that take one.
+    check: Option<CheckValue>,
+}
+
+/// Checks that a value parses, with the reason if not.
+type CheckValue = fn(&str) -> Result<(), String>;
+
+impl DayOption {
+    /// An option without a value, read with [`DayArgs::flag`].
+    pub const fn flag(name: &'static str) -> Self {
+        Self { name, check: None }
+    }
+
+    /// An option with a value of type `T`, read with [`DayArgs::value`] or [`DayArgs::values`].
+    pub const fn value<T: FromStr<Err: Display>>(name: &'static str) -> Self {
+        Self {
+            name,
+            check: Some(check_value::<T>),
+        }
+    }
+}
+
+impl Display for DayOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.check {
+            Some(_) => write!(f, "{} <value>", self.name),
+            None => f.write_str(self.name),
+        }
+    }
+}
+
+fn check_value<T: FromStr<Err: Display>>(value: &str) -> Result<(), String> {
+    value.parse::<T>().map(drop).map_err(|e| e.to_string())
+}
+
+static DAY_ARGS: OnceLock<DayArgs> = OnceLock::new();
+
+/// The day options of the running solution binary. Empty in tests and when none were passed.
+pub fn day_args() -> &'static DayArgs {
+    DAY_ARGS.get_or_init(DayArgs::default)
+}
+
+/// Splits command-line arguments at the first `--` into the template's options and the
+/// [`DayArgs`] after it.
+pub fn split_day_args(mut args: Vec<OsString>) -> (Vec<OsString>, DayArgs) {
+    match args.iter().position(|arg| arg == "--") {
+        Some(i) => {
+            let day_args = args.split_off(i + 1);
+            args.pop();
+            let day_args = day_args.iter().map(|arg| arg.to_string_lossy());
+            (args, DayArgs::new(day_args))
+        }
+        None => (args, DayArgs::default()),
+    }
+}
+
+impl SolutionArgs {
+    /// Parse arguments from a list of strings, not including the binary name.
+    pub fn parse(args: Vec<OsString>) -> Result<Self, pico_args::Error> {
+        let (args, day_args) = split_day_args(args);
+        let mut args = pico_args::Arguments::from_vec(args);
+
+        let parsed = Self {
+            part: args.opt_value_from_fn("--part", parse_part)?,
+            input: args.opt_value_from_os_str("--input", |s| Ok::<_, String>(PathBuf::from(s)))?,
+            format: args.opt_value_from_str("--format")?.unwrap_or_default(),
+            quiet: args.contains("--quiet"),
+            time: args.contains("--time"),
+            bench: BenchOptions::from_args(&mut args)?,
+            perf: args.contains("--perf"),
+            submit: args.opt_value_from_fn("--submit", parse_part)?,
+            viz: VizOptions::from_args(&mut args)?,
+            day_args,
+        };
+
+        let remaining = args.finish();
+        if !remaining.is_empty() {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!("unexpected argument(s) {remaining:?}"),
+            });
+        }
+
+        if let (Some(part), Some(submit)) = (parsed.part, parsed.submit)
+            && part != submit
+        {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: format!("cannot submit part {submit} when only running part {part}"),
+            });
+        }
+
+        if parsed.viz.is_some() && !cfg!(feature = "viz") {
+            return Err(pico_args::Error::ArgumentParsingFailed {
+                cause: "`--viz` and `--viz-out` require the `viz` feature, use `cargo solve <day> --viz`".into(),
+            });
+        }
+
+        Ok(parsed)
+    }
+
+    /// Parse the arguments of the current process, check the day options against the `options`
+    /// the day accepts and make them available to [`day_args`]. Prints the help text for `--help`
+    /// and exits, as well as on malformed arguments.
+    pub fn from_env(options: &[DayOption]) -> Self {
+        let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+
+        if args
+            .iter()
+            .take_while(|x| *x != "--")
+            .any(|x| x == "-h" || x == "--help")
+        {
+            print!("{HELP}");
+            if !options.is_empty() {
+                println!("\n  This day accepts:");
+                for option in options {
+                    println!("    {option}");
+                }
+            }
+            process::exit(0);
+        }
+
+        let parsed = Self::parse(args)
+            .map_err(|e| e.to_string())
+            .and_then(|parsed| parsed.day_args.validate(options).map(|()| parsed))
+            .unwrap_or_else(|e| exit_with_error(&e));
+        let _ = DAY_ARGS.set(parsed.day_args.clone());
+        parsed
+    }
+
+    /// Run the `setup` of a day, which loads what its day options refer to before any part runs.
+    /// Exits with its error like on malformed arguments.
+    pub fn setup(&self, setup: fn() -> Result<(), String>) {
+        if let Err(e) = setup() {
+            exit_with_error(&e);
+        }
+    }
+
+    /// Whether the given part was selected to run.
+    pub fn runs_part(&self, part: u8) -> bool {
+        self.part.is_none_or(|x| x == part)
+    }
+
+    /// Whether progress and formatted results are printed, as opposed to plain results.
+    pub fn is_interactive(&self) -> bool {
+        self.format == OutputFormat::Text && !self.quiet
+    }
+
+    /// Read the puzzle input, either from `--input` or from `data/inputs`.
+    pub fn read_input(&self, day: Day) -> String {
+        match &self.input {
+            Some(path) => fs::read_to_string(path).unwrap_or_else(|e| {
+                eprintln!(
+                    "Error: could not read input file \"{}\": {e}.",
+                    path.display()
+                );
+                process::exit(1);
+            }),
+            None => read_file("inputs", day),
+        }
+    }
+}
+
+fn exit_with_error(e: &str) -> ! {
+    eprintln!("Error: {e}.");
+    eprintln!("Run with `--help` to list the available options.");
+    process::exit(1);
+}
+
+/// Parse a part number, accepting only `1` and `2`.
+pub fn parse_part(s: &str) -> Result<u8, String> {
+    match s {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("expected part `1` or `2`, got `{s}`")),
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{DayArgs, DayOption, OutputFormat, SolutionArgs};
+    use crate::template::runner::DEFAULT_VIZ_DELAY;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn parse(args: &[&str]) -> Result<SolutionArgs, pico_args::Error> {
+        SolutionArgs::parse(args.iter().map(OsString::from).collect())
+    }
+
+    #[test]
+    fn parses_defaults() {
+        let args = parse(&[]).unwrap();
+        assert_eq!(args, SolutionArgs::default());
+        assert!(args.runs_part(1));
+        assert!(args.runs_part(2));
+        assert!(args.is_interactive());
+    }
+
+    #[test]
+    fn parses_all_options() {
+        let args = parse(&[
+            "--part",
+            "2",
+            "--input",
+            "data/examples/01.txt",
+            "--format",
+            "json",
+            "--quiet",
+            "--time",
+            "--budget",
+            "10ms",
+            "--perf",
+            "--submit",
+            "2",
+        ])
+        .unwrap();
+
+        assert_eq!(args.part, Some(2));
+        assert_eq!(args.input, Some(PathBuf::from("data/examples/01.txt")));
+        assert_eq!(args.format, OutputFormat::Json);
+        assert!(args.quiet);
+        assert!(args.time);
+        assert_eq!(args.bench.budget, std::time::Duration::from_millis(10));
+        assert!(args.perf);
+        assert_eq!(args.submit, Some(2));
+        assert!(!args.runs_part(1));
+        assert!(!args.is_interactive());
+    }
+
+    #[test]
+    fn rejects_invalid_parts() {
+        assert!(parse(&["--part", "3"]).is_err());
+        assert!(parse(&["--submit", "x"]).is_err());
+        assert!(parse(&["--submit"]).is_err());
+    }
+
+    #[test]
+    fn rejects_submitting_unselected_part() {
+        assert!(parse(&["--part", "1", "--submit", "2"]).is_err());
+    }
+
+    #[test]
+    fn parses_viz_options() {
+        assert!(parse(&["--viz-delay", "10ms"]).is_err());
+        assert!(parse(&["--viz-out", "day4.gif"]).is_err());
+
+        let args = parse(&["--viz", "--viz-delay", "10ms"]);
+        let exported = parse(&["--viz-out", "out/day4.cast"]);
+        if cfg!(feature = "viz") {
+            let viz = args.unwrap().viz.unwrap();
+            assert!(viz.terminal);
+            assert_eq!(viz.delay, Duration::from_millis(10));
+
+            let viz = exported.unwrap().viz.unwrap();
+            assert!(!viz.terminal);
+            assert_eq!(viz.out, Some(PathBuf::from("out/day4.cast")));
+            assert_eq!(viz.delay, DEFAULT_VIZ_DELAY);
+        } else {
+            assert!(args.is_err());
+            assert!(exported.is_err());
+        }
+    }
+
+    #[test]
+    fn passes_day_options_through() {
+        let args = parse(&["--part", "2", "--", "--layouts", "--n", "10", "--n", "20"]).unwrap();
+        assert_eq!(args.part, Some(2));
+        assert_eq!(
+            args.day_args,
+            DayArgs::new(["--layouts", "--n", "10", "--n", "20"])
+        );
+
+        let day_args = &args.day_args;
+        assert!(day_args.flag("--layouts"));
+        assert!(!day_args.flag("--explain"));
+        assert_eq!(day_args.value::<u32>("--n"), Some(20));
+        assert_eq!(day_args.values::<u32>("--n"), vec![10, 20]);
+        assert_eq!(day_args.value::<u32>("--k"), None);
+
+        // Template options after `--` are left to the day as well
+        assert!(!parse(&["--", "--time"]).unwrap().time);
+    }
+
+    #[test]
+    fn validates_day_options() {
+        let options = [DayOption::flag("--layouts"), DayOption::value::<u32>("--n")];
+        let validate = |args: &[&str]| DayArgs::new(args.iter().copied()).validate(&options);
+
+        assert_eq!(validate(&[]), Ok(()));
+        assert_eq!(validate(&["--n", "10", "--layouts", "--n", "20"]), Ok(()));
+        assert!(validate(&["--n"]).unwrap_err().contains("needs a value"));
+        assert!(
+            validate(&["--n", "ten"])
+                .unwrap_err()
+                .contains("invalid value `ten`")
+        );
+        assert!(
+            validate(&["--layout"])
+                .unwrap_err()
+                .contains("unknown day option `--layout`")
+        );
+        assert!(validate(&["--layouts", "10"]).is_err());
+        assert!(DayArgs::new(["--n"]).validate(&[]).is_err());
+        assert_eq!(options[1].to_string(), "--n <value>");
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(parse(&["--fast"]).is_err());
+        assert!(parse(&["--format", "yaml"]).is_err());
+    }
+}
@@ -1,7 +1,7 @@
-use std::{collections::HashMap, fs, io::Error, str::FromStr};
+use std::{collections::HashMap, fs, io::Error, str::FromStr, time::Duration};
 use tinyjson::JsonValue;
 
-use crate::template::Day;
+use crate::template::{Day, runner::BenchOptions};
 
 static TIMINGS_FILE_PATH: &str = "./data/timings.json";
 
@@ -12,6 +12,55 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// The options the timings were benched with. `None` for timings stored before these were recorded.
+    pub bench: Option<BenchOptions>,
+    /// The single part that was run, `None` if both were. Not persisted.
+    pub part: Option<u8>,
+}
+
+impl Timing {
+    /// Fill in the part that was not run from a previously stored timing of the same day.
+    /// The bench options are only kept if both parts were benched with them.
+    fn with_missing_part_from(&self, stored: &Timing) -> Timing {
+        let mut timing = self.clone();
+
+        let kept = match self.part {
+            Some(1) => {
+                timing.part_2.clone_from(&stored.part_2);
+                &stored.part_2
+            }
+            Some(2) => {
+                timing.part_1.clone_from(&stored.part_1);
+                &stored.part_1
+            }
+            _ => return timing,
+        };
+
+        if kept.is_some() && stored.bench != self.bench {
+            timing.bench = None;
+        }
+        timing.total_nanos += kept
+            .as_deref()
+            .and_then(parse_duration_nanos)
+            .unwrap_or(0_f64);
+        timing.part = None;
+        timing
+    }
+}
+
+/// Parse a formatted duration like `74.13ms` into nanoseconds.
+pub fn parse_duration_nanos(s: &str) -> Option<f64> {
+    fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
+        s.split(postfix).next()?.parse().ok()
+    }
+
+    // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+    match s {
+        s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
+        s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
+        s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
+        s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
+    }
 }
 
 /// Represents benchmark times for a set of days.
@@ -38,11 +87,15 @@ impl Timings {
     }
 
     /// Merge two sets of timings, overwriting `self` with `other` if present.
+    /// If only a single part of a day was run, the stored timing of the other part is kept.
     pub fn merge(&self, new: &Self) -> Self {
         let mut data: Vec<Timing> = vec![];
 
         for timing in &new.data {
-            data.push(timing.clone());
+            match self.data.iter().find(|t| t.day == timing.day) {
+                Some(stored) => data.push(timing.with_missing_part_from(stored)),
+                None => data.push(timing.clone()),
+            }
         }
 
         for timing in &self.data {
@@ -51,7 +104,7 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        data.sort_unstable_by_key(|a| a.day);
         Timings { data }
     }
 
@@ -65,6 +118,16 @@ impl Timings {
             .iter()
             .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some())
     }
+
+    pub fn is_part_complete(&self, day: Day, part: u8) -> bool {
+        self.data.iter().any(|t| {
+            t.day == day
+                && match part {
+                    1 => t.part_1.is_some(),
+                    _ => t.part_2.is_some(),
+                }
+        })
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -133,6 +196,14 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        map.insert(
+            "bench".into(),
+            match &value.bench {
+                Some(x) => JsonValue::from(x),
+                None => JsonValue::Null,
+            },
+        );
+
         JsonValue::Object(map)
     }
 }
@@ -166,11 +237,68 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // NOTE: `bench` is optional to keep reading timings stored by older versions.
+        let bench = match json.get("bench") {
+            None | Some(JsonValue::Null) => None,
+            Some(v) => Some(BenchOptions::try_from(v)?),
+        };
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            bench,
+            part: None,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&BenchOptions> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &BenchOptions) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert(
+            "budget_nanos".into(),
+            JsonValue::Number(value.budget.as_nanos() as f64),
+        );
+        map.insert(
+            "min_samples".into(),
+            JsonValue::Number(value.min_samples as f64),
+        );
+        map.insert(
+            "max_samples".into(),
+            JsonValue::Number(value.max_samples as f64),
+        );
+        map.insert("warmup".into(), JsonValue::Number(value.warmup as f64));
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for BenchOptions {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected timing.bench to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .ok_or(format!("Expected timing.bench.{key} to be a number."))
+        };
+
+        Ok(BenchOptions {
+            budget: Duration::from_nanos(get_number("budget_nanos")? as u64),
+            min_samples: get_number("min_samples")? as u128,
+            max_samples: get_number("max_samples")? as u128,
+            warmup: get_number("warmup")? as u128,
         })
     }
 }
@@ -191,18 +319,24 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    bench: None,
+                    part: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    bench: None,
+                    part: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    bench: None,
+                    part: None,
                 },
             ],
         }
@@ -221,6 +355,18 @@ mod tests {
             assert_eq!(timing.part_1, Some("1ms".to_string()));
             assert_eq!(timing.part_2, None);
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
+            assert_eq!(timing.bench, None);
+        }
+
+        #[test]
+        fn handles_bench_options() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000, "bench": { "budget_nanos": 500000000, "min_samples": 5, "max_samples": 50, "warmup": 2 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let bench = timings.data[0].bench.unwrap();
+            assert_eq!(bench.budget, std::time::Duration::from_millis(500));
+            assert_eq!(bench.min_samples, 5);
+            assert_eq!(bench.max_samples, 50);
+            assert_eq!(bench.warmup, 2);
         }
 
         #[test]
@@ -247,6 +393,7 @@ mod tests {
 
     mod serialization {
         use super::get_mock_timings;
+        use crate::template::{runner::BenchOptions, timings::Timings};
         use std::collections::HashMap;
         use tinyjson::JsonValue;
 
@@ -266,6 +413,16 @@ mod tests {
                 3
             );
         }
+
+        #[test]
+        fn round_trips_bench_options() {
+            let mut timings = get_mock_timings();
+            timings.data[0].bench = Some(BenchOptions::default());
+            let value = JsonValue::from(timings.clone());
+            let parsed = Timings::try_from(value.stringify().unwrap()).unwrap();
+            assert_eq!(parsed.data[0].bench, Some(BenchOptions::default()));
+            assert_eq!(parsed.data[1].bench, None);
+        }
     }
 
     mod is_day_complete {
@@ -282,10 +439,12 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    bench: None,
+                    part: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -296,10 +455,12 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    bench: None,
+                    part: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -310,16 +471,19 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    bench: None,
+                    part: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
     }
 
     mod merge {
         use crate::{
             day,
+            template::runner::BenchOptions,
             template::timings::{Timing, Timings},
         };
 
@@ -334,6 +498,8 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    bench: None,
+                    part: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -354,6 +520,8 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    bench: None,
+                    part: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -365,6 +533,81 @@ mod tests {
             assert_eq!(merged.data[2].day, day!(4));
         }
 
+        #[test]
+        fn keeps_stored_part_for_single_part_runs() {
+            let timings = get_mock_timings();
+
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    part_1: None,
+                    part_2: Some("10ms".into()),
+                    total_nanos: 1e+7,
+                    bench: None,
+                    part: Some(2),
+                }],
+            };
+            let merged = timings.merge(&other);
+
+            assert_eq!(merged.data.len(), 3);
+            assert_eq!(merged.data[1].part_1, Some("30ms".into()));
+            assert_eq!(merged.data[1].part_2, Some("10ms".into()));
+            assert_eq!(merged.data[1].total_nanos, 4e+7);
+            assert_eq!(merged.data[1].part, None);
+        }
+
+        #[test]
+        fn keeps_bench_options_only_if_both_parts_share_them() {
+            let mut timings = get_mock_timings();
+            let stored = BenchOptions {
+                warmup: 3,
+                ..BenchOptions::default()
+            };
+            timings.data[1].bench = Some(stored);
+
+            let run = |bench: BenchOptions| Timings {
+                data: vec![Timing {
+                    day: day!(2),
+                    part_1: None,
+                    part_2: Some("10ms".into()),
+                    total_nanos: 1e+7,
+                    bench: Some(bench),
+                    part: Some(2),
+                }],
+            };
+
+            let merged = timings.merge(&run(stored));
+            assert_eq!(merged.data[1].bench, Some(stored));
+            let merged = timings.merge(&run(BenchOptions::default()));
+            assert_eq!(merged.data[1].bench, None);
+
+            // Without a stored timing of the other part, the options apply to the whole day
+            timings.data[1].part_1 = None;
+            let merged = timings.merge(&run(BenchOptions::default()));
+            assert_eq!(merged.data[1].bench, Some(BenchOptions::default()));
+        }
+
+        #[test]
+        fn handles_single_part_runs_without_stored_timing() {
+            let timings = get_mock_timings();
+
+            let other = Timings {
+                data: vec![Timing {
+                    day: day!(3),
+                    part_1: Some("1ms".into()),
+                    part_2: None,
+                    total_nanos: 1e+6,
+                    bench: None,
+                    part: Some(1),
+                }],
+            };
+            let merged = timings.merge(&other);
+
+            assert_eq!(merged.data.len(), 4);
+            assert_eq!(merged.data[2].part_1, Some("1ms".into()));
+            assert_eq!(merged.data[2].part_2, None);
+        }
+
         #[test]
         fn handles_empty_timings() {
             let timings = Timings::default();
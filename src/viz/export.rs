@@ -0,0 +1,581 @@
+//! Viewers that record a run to files instead of showing it, selected by the extension of the
+//! output path:
+//! - `.svg`: frames as coloured text and scenes as vector drawings;
+//! - `.ppm` and `.png`: frames as images with one square per cell, scenes are skipped;
+//! - `.cast`: an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) recording of the
+//!   terminal animation, scenes are skipped.
+//!
+//! Recordings with more than one image are written as a numbered sequence next to the output
+//! path, e.g. `out-0001.png`, `out-0002.png`, ...
+
+use super::{Color, Frame, Scene, Shape, Viewer};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io};
+use tinyjson::JsonValue;
+
+/// The colours of cells and shapes that keep the `Default` colour.
+const BACKGROUND: (u8, u8, u8) = (24, 24, 24);
+const FOREGROUND: (u8, u8, u8) = (204, 204, 204);
+
+/// The side length in pixels of a cell in raster images.
+const PIXELS_PER_CELL: usize = 4;
+
+/// The size in pixels of the larger side of a scene drawing.
+const SCENE_SIZE: f64 = 800.0;
+
+/// A file format to export to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Svg,
+    Ppm,
+    Png,
+    Cast,
+}
+
+impl Format {
+    /// The format for the extension of `path`.
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => Ok(Self::Svg),
+            Some("ppm") => Ok(Self::Ppm),
+            Some("png") => Ok(Self::Png),
+            Some("cast") => Ok(Self::Cast),
+            _ => Err(format!(
+                "cannot export to \"{}\", expected a .svg, .ppm, .png or .cast file",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// A viewer that writes everything it receives to `path` when it is detached. `delay` is the time
+/// each frame is shown for in asciicast recordings.
+pub fn exporter(path: PathBuf, delay: Duration) -> Result<Box<dyn Viewer>, String> {
+    let format = Format::from_path(&path)?;
+    Ok(match format {
+        Format::Svg | Format::Ppm | Format::Png => Box::new(Images {
+            path,
+            format,
+            images: Vec::new(),
+        }),
+        Format::Cast => Box::new(Cast {
+            path,
+            delay,
+            size: (0, 0),
+            events: Vec::new(),
+        }),
+    })
+}
+
+/// Collects one image per frame, and per scene for SVG.
+struct Images {
+    path: PathBuf,
+    format: Format,
+    images: Vec<Vec<u8>>,
+}
+
+impl Viewer for Images {
+    fn show(&mut self, frame: &Frame) {
+        let image = match self.format {
+            Format::Svg => frame_to_svg(frame).into_bytes(),
+            Format::Ppm => Raster::from_frame(frame).to_ppm(),
+            _ => Raster::from_frame(frame).to_png(),
+        };
+        self.images.push(image);
+    }
+
+    fn draw(&mut self, scene: &Scene) {
+        if self.format == Format::Svg {
+            self.images.push(scene_to_svg(scene).into_bytes());
+        }
+    }
+
+    fn finish(&mut self) {
+        report(
+            &self.path,
+            self.images.len(),
+            write_sequence(&self.path, &self.images),
+        );
+    }
+}
+
+/// Writes a single image to `path`, and more than one as a numbered sequence.
+fn write_sequence(path: &Path, images: &[Vec<u8>]) -> io::Result<()> {
+    if let [image] = images {
+        return write(path, image);
+    }
+    for (i, image) in images.iter().enumerate() {
+        write(&sequence_path(path, i + 1), image)?;
+    }
+    Ok(())
+}
+
+/// Writes `contents` to `path`, creating its directory if needed.
+fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+}
+
+/// The path of the `n`-th image of a sequence, e.g. `out-0001.png` for `out.png`.
+pub fn sequence_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}-{n:04}.{ext}"))
+}
+
+fn report(path: &Path, count: usize, result: io::Result<()>) {
+    match result {
+        Ok(()) if count == 1 => eprintln!("viz: wrote {}", path.display()),
+        Ok(()) if count > 1 => eprintln!(
+            "viz: wrote {count} images to {}",
+            sequence_path(path, 1).display()
+        ),
+        Ok(()) => eprintln!("viz: nothing to write to {}", path.display()),
+        Err(e) => eprintln!("viz: failed to write {}: {e}", path.display()),
+    }
+}
+
+fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn rgb(color: Color, default: (u8, u8, u8)) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Default => default,
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders a frame as rows of monospace text on coloured backgrounds, with the caption below.
+fn frame_to_svg(frame: &Frame) -> String {
+    const CELL_WIDTH: f64 = 9.6;
+    const CELL_HEIGHT: f64 = 16.0;
+
+    let cells = &frame.cells;
+    let width = cells.width() as f64 * CELL_WIDTH;
+    let height = (cells.height() + 1) as f64 * CELL_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.0}" font-family="monospace" font-size="{CELL_HEIGHT}" xml:space="preserve">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(BACKGROUND)
+    );
+
+    for (r, row) in cells.rows().enumerate() {
+        let y = r as f64 * CELL_HEIGHT;
+
+        // Backgrounds, one rectangle per run of the same colour
+        let mut x = 0;
+        for run in row.chunk_by(|a, b| a.bg == b.bg) {
+            if let Color::Rgb(..) = run[0].bg {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{y}" width="{}" height="{CELL_HEIGHT}" fill="{}"/>"#,
+                    x as f64 * CELL_WIDTH,
+                    run.len() as f64 * CELL_WIDTH,
+                    hex(rgb(run[0].bg, BACKGROUND))
+                );
+            }
+            x += run.len();
+        }
+
+        // Characters, one span per run of the same colour
+        let _ = write!(svg, r#"<text y="{}">"#, y + CELL_HEIGHT * 0.8);
+        for run in row.chunk_by(|a, b| a.fg == b.fg) {
+            let text: String = run.iter().map(|cell| cell.ch).collect();
+            let _ = write!(
+                svg,
+                r#"<tspan fill="{}">{}</tspan>"#,
+                hex(rgb(run[0].fg, FOREGROUND)),
+                escape_xml(&text)
+            );
+        }
+        svg.push_str("</text>");
+    }
+
+    let _ = write!(
+        svg,
+        r#"<text y="{}" fill="{}">{}</text></svg>"#,
+        height - CELL_HEIGHT * 0.2,
+        hex(FOREGROUND),
+        escape_xml(&frame.caption)
+    );
+    svg
+}
+
+/// Draws a scene scaled to fit [`SCENE_SIZE`], with the caption below.
+fn scene_to_svg(scene: &Scene) -> String {
+    const MARGIN: f64 = 10.0;
+    const CAPTION_HEIGHT: f64 = 24.0;
+
+    let ((min_x, min_y), (max_x, max_y)) = scene.bounds().unwrap_or(((0.0, 0.0), (1.0, 1.0)));
+    let scale = SCENE_SIZE / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let width = (max_x - min_x) * scale + 2.0 * MARGIN;
+    let height = (max_y - min_y) * scale + 2.0 * MARGIN + CAPTION_HEIGHT;
+    let project = |(x, y): (f64, f64)| ((x - min_x) * scale + MARGIN, (y - min_y) * scale + MARGIN);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" font-family="monospace" font-size="16">"#
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(BACKGROUND)
+    );
+
+    for shape in &scene.shapes {
+        match shape {
+            Shape::Polygon {
+                points,
+                stroke,
+                fill,
+            } => {
+                let points = points
+                    .iter()
+                    .map(|&p| {
+                        let (x, y) = project(p);
+                        format!("{x:.2},{y:.2}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let fill = match fill {
+                    Color::Default => "none".to_string(),
+                    &color => hex(rgb(color, FOREGROUND)),
+                };
+                let _ = write!(
+                    svg,
+                    r#"<polygon points="{points}" fill="{fill}" stroke="{}" stroke-width="1.5"/>"#,
+                    hex(rgb(*stroke, FOREGROUND))
+                );
+            }
+            Shape::Line { from, to, stroke } => {
+                let ((x1, y1), (x2, y2)) = (project(*from), project(*to));
+                let _ = write!(
+                    svg,
+                    r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" stroke="{}"/>"#,
+                    hex(rgb(*stroke, FOREGROUND))
+                );
+            }
+            Shape::Dot { at, color } => {
+                let (x, y) = project(*at);
+                let _ = write!(
+                    svg,
+                    r#"<circle cx="{x:.2}" cy="{y:.2}" r="2.5" fill="{}"/>"#,
+                    hex(rgb(*color, FOREGROUND))
+                );
+            }
+        }
+    }
+
+    let _ = write!(
+        svg,
+        r#"<text x="{MARGIN}" y="{:.0}" fill="{}">{}</text></svg>"#,
+        height - MARGIN,
+        hex(FOREGROUND),
+        escape_xml(&scene.caption)
+    );
+    svg
+}
+
+/// An RGB image.
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+impl Raster {
+    /// One square per cell, in its background colour, or its foreground colour for visible
+    /// characters on the default background. Captions are not drawn.
+    fn from_frame(frame: &Frame) -> Self {
+        let cells = &frame.cells;
+        let (width, height) = (
+            cells.width() * PIXELS_PER_CELL,
+            cells.height() * PIXELS_PER_CELL,
+        );
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y / PIXELS_PER_CELL, x / PIXELS_PER_CELL)))
+            .map(|pos| {
+                let cell = cells[pos];
+                match cell.bg {
+                    Color::Rgb(r, g, b) => (r, g, b),
+                    Color::Default if cell.ch.is_whitespace() => BACKGROUND,
+                    Color::Default => rgb(cell.fg, FOREGROUND),
+                }
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|&(r, g, b)| [r, g, b])
+    }
+
+    /// Binary PPM (`P6`).
+    fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.bytes());
+        out
+    }
+
+    /// 8-bit RGB PNG with uncompressed deflate blocks, to avoid an image dependency.
+    fn to_png(&self) -> Vec<u8> {
+        // Each scanline starts with its filter type, 0 for none
+        let mut scanlines = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        let bytes: Vec<u8> = self.bytes().collect();
+        for row in bytes.chunks(self.width * 3).take(self.height) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // Bit depth 8, colour type RGB, default compression, filtering and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream of `data` in stored (uncompressed) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = u16::MAX as usize;
+
+    let mut out = vec![0x78, 0x01];
+    let blocks = data.len().div_ceil(BLOCK).max(1);
+    for i in 0..blocks {
+        let block = &data[i * BLOCK..data.len().min((i + 1) * BLOCK)];
+        let len = block.len() as u16;
+        out.push(u8::from(i + 1 == blocks));
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// Records frames as the output of a terminal playing them with a fixed delay.
+struct Cast {
+    path: PathBuf,
+    delay: Duration,
+    /// The largest frame so far, as `(columns, rows)` including the caption.
+    size: (usize, usize),
+    /// The text written to the terminal per frame.
+    events: Vec<String>,
+}
+
+impl Viewer for Cast {
+    fn show(&mut self, frame: &Frame) {
+        let columns = frame.cells.width().max(frame.caption.chars().count());
+        self.size = (
+            self.size.0.max(columns),
+            self.size.1.max(frame.cells.height() + 1),
+        );
+
+        // Recorded terminal output has carriage returns before each line feed
+        let prefix = if self.events.is_empty() {
+            "\x1b[2J\x1b[H"
+        } else {
+            "\x1b[H"
+        };
+        let text = frame.to_ansi().replace('\n', "\r\n");
+        self.events.push(format!("{prefix}{text}\x1b[J"));
+    }
+
+    fn finish(&mut self) {
+        let recording = cast_recording(self.size, self.delay, &self.events);
+        let count = usize::from(!self.events.is_empty());
+        report(&self.path, count, write(&self.path, recording.as_bytes()));
+    }
+}
+
+/// An asciicast v2 file: a JSON header line followed by one `[time, "o", text]` line per event.
+fn cast_recording((width, height): (usize, usize), delay: Duration, events: &[String]) -> String {
+    let header = JsonValue::Object(
+        [
+            ("version".to_string(), JsonValue::Number(2.0)),
+            ("width".to_string(), JsonValue::Number(width.max(1) as f64)),
+            (
+                "height".to_string(),
+                JsonValue::Number(height.max(1) as f64),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    );
+
+    let mut out = header.stringify().unwrap_or_default();
+    out.push('\n');
+    for (i, text) in events.iter().enumerate() {
+        let event = JsonValue::Array(vec![
+            JsonValue::Number(i as f64 * delay.as_secs_f64()),
+            JsonValue::String("o".to_string()),
+            JsonValue::String(text.clone()),
+        ]);
+        out.push_str(&event.stringify().unwrap_or_default());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, Raster, adler32, cast_recording, crc32, frame_to_svg, scene_to_svg};
+    use super::{sequence_path, zlib_stored};
+    use crate::grid::Grid;
+    use crate::viz::{Cell, Color, Frame, Scene, Shape};
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn frame() -> Frame {
+        let grid = Grid::parse("#.\n.#", |c| c == '#');
+        Frame::from_grid(&grid, |_, &on| {
+            if on {
+                Cell::new('#').fg(Color::Rgb(255, 0, 0))
+            } else {
+                Cell::new(' ').bg(Color::Rgb(0, 0, 255))
+            }
+        })
+        .caption("a < b")
+    }
+
+    #[test]
+    fn selects_format_by_extension() {
+        assert_eq!(
+            Format::from_path(Path::new("out/day9.svg")),
+            Ok(Format::Svg)
+        );
+        assert_eq!(Format::from_path(Path::new("day4.png")), Ok(Format::Png));
+        assert_eq!(Format::from_path(Path::new("day4.ppm")), Ok(Format::Ppm));
+        assert_eq!(Format::from_path(Path::new("run.cast")), Ok(Format::Cast));
+        assert!(Format::from_path(Path::new("run.gif")).is_err());
+        assert!(Format::from_path(Path::new("run")).is_err());
+
+        assert_eq!(
+            sequence_path(Path::new("out/day4.png"), 12),
+            Path::new("out/day4-0012.png")
+        );
+    }
+
+    #[test]
+    fn rasterises_cells() {
+        let raster = Raster::from_frame(&frame());
+        assert_eq!((raster.width, raster.height), (8, 8));
+        assert_eq!(raster.pixels[0], (255, 0, 0));
+        assert_eq!(raster.pixels[4], (0, 0, 255));
+        assert_eq!(raster.pixels[8 * 4 + 4], (255, 0, 0));
+
+        let ppm = raster.to_ppm();
+        assert!(ppm.starts_with(b"P6\n8 8\n255\n"));
+        assert_eq!(ppm.len(), 11 + 8 * 8 * 3);
+    }
+
+    #[test]
+    fn encodes_png() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        // Stored blocks hold at most 65535 bytes
+        let data = vec![7; 70_000];
+        let zlib = zlib_stored(&data);
+        assert_eq!(zlib.len(), 2 + 2 * 5 + data.len() + 4);
+        assert_eq!(&zlib[2..7], &[0, 0xff, 0xff, 0, 0]);
+
+        let png = Raster::from_frame(&frame()).to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x08\0\0\0\x08"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn renders_svg() {
+        let svg = frame_to_svg(&frame());
+        assert!(svg.contains(r##"<tspan fill="#ff0000">#</tspan>"##));
+        assert!(svg.contains(r##"<rect x="9.6" y="0" width="9.6" height="16" fill="#0000ff"/>"##));
+        assert!(svg.contains("a &lt; b"));
+
+        let scene: Scene = [
+            Shape::Polygon {
+                points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)],
+                stroke: Color::GREEN,
+                fill: Color::Default,
+            },
+            Shape::Dot {
+                at: (10.0, 5.0),
+                color: Color::RED,
+            },
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(scene.bounds(), Some(((0.0, 0.0), (10.0, 5.0))));
+
+        let svg = scene_to_svg(&scene);
+        assert!(svg.contains(r#"width="820" height="444""#));
+        assert!(svg.contains(r#"points="10.00,10.00 810.00,10.00 810.00,410.00" fill="none""#));
+        assert!(svg.contains(r#"<circle cx="810.00" cy="410.00""#));
+    }
+
+    #[test]
+    fn records_asciicast() {
+        let events = ["\x1b[Hab\r\n".to_string(), "cd".to_string()];
+        let cast = cast_recording((2, 2), Duration::from_millis(500), &events);
+        let lines: Vec<&str> = cast.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains(r#""version":2"#));
+        assert!(lines[0].contains(r#""width":2"#));
+        assert_eq!(lines[1], r#"[0,"o","\u001b[Hab\r\n"]"#);
+        assert_eq!(lines[2], r#"[0.5,"o","cd"]"#);
+    }
+}
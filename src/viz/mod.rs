@@ -0,0 +1,382 @@
+//! Frames of solution state, shown as an animation in the terminal or exported to files.
+//!
+//! Solutions describe their state with [`frame`] for grids and [`scene`] for vector drawings,
+//! whose closures only run while a viewer is attached, i.e. when built with the `viz` feature and
+//! run with `--viz` or `--viz-out`. Without the feature [`frame`], [`scene`] and [`is_active`]
+//! compile to nothing, so benchmarks are unaffected.
+
+pub mod export;
+
+use crate::grid::{Grid, Pos};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::time::Duration;
+
+/// A terminal colour. `Default` leaves the terminal's own colour in place.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Color {
+    #[default]
+    Default,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    pub const RED: Color = Color::Rgb(230, 70, 60);
+    pub const GREEN: Color = Color::Rgb(80, 200, 90);
+    pub const YELLOW: Color = Color::Rgb(240, 200, 60);
+    pub const BLUE: Color = Color::Rgb(70, 130, 230);
+    pub const MAGENTA: Color = Color::Rgb(200, 90, 200);
+    pub const CYAN: Color = Color::Rgb(70, 200, 210);
+    pub const WHITE: Color = Color::Rgb(240, 240, 240);
+    pub const GRAY: Color = Color::Rgb(110, 110, 110);
+
+    /// A colour from a fixed palette of distinguishable colours, e.g. one per piece.
+    pub fn palette(index: usize) -> Color {
+        const PALETTE: [Color; 6] = [
+            Color::RED,
+            Color::GREEN,
+            Color::YELLOW,
+            Color::BLUE,
+            Color::MAGENTA,
+            Color::CYAN,
+        ];
+        PALETTE[index % PALETTE.len()]
+    }
+}
+
+/// A character with foreground and background colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    pub fn new(ch: char) -> Self {
+        Self {
+            ch,
+            fg: Color::Default,
+            bg: Color::Default,
+        }
+    }
+
+    pub fn fg(self, fg: Color) -> Self {
+        Self { fg, ..self }
+    }
+
+    pub fn bg(self, bg: Color) -> Self {
+        Self { bg, ..self }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new(' ')
+    }
+}
+
+/// A snapshot of a solution's state: a grid of coloured cells and a caption.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub cells: Grid<Cell>,
+    pub caption: String,
+}
+
+impl Frame {
+    /// A frame showing `grid`, with `f` choosing each cell's appearance.
+    pub fn from_grid<T>(grid: &Grid<T>, mut f: impl FnMut(Pos, &T) -> Cell) -> Self {
+        Self {
+            cells: Grid::from_fn(grid.width(), grid.height(), |pos| f(pos, &grid[pos])),
+            caption: String::new(),
+        }
+    }
+
+    pub fn caption(self, caption: impl Into<String>) -> Self {
+        Self {
+            caption: caption.into(),
+            ..self
+        }
+    }
+
+    /// Renders the frame with ANSI colour escapes, one line per row followed by the caption.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.rows() {
+            let mut current = (Color::Default, Color::Default);
+            for cell in row {
+                if (cell.fg, cell.bg) != current {
+                    out.push_str("\x1b[0m");
+                    if let Color::Rgb(r, g, b) = cell.fg {
+                        let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+                    }
+                    if let Color::Rgb(r, g, b) = cell.bg {
+                        let _ = write!(out, "\x1b[48;2;{r};{g};{b}m");
+                    }
+                    current = (cell.fg, cell.bg);
+                }
+                out.push(cell.ch);
+            }
+            if current != (Color::Default, Color::Default) {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out.push_str(&self.caption);
+        out.push('\n');
+        out
+    }
+}
+
+/// A point in solution coordinates, as used by [`Scene`].
+pub type Point = (f64, f64);
+
+/// A shape of a [`Scene`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A closed polygon through `points`. A `Default` fill leaves it unfilled.
+    Polygon {
+        points: Vec<Point>,
+        stroke: Color,
+        fill: Color,
+    },
+    Line {
+        from: Point,
+        to: Point,
+        stroke: Color,
+    },
+    /// A marker of the same size at every scale.
+    Dot { at: Point, color: Color },
+}
+
+/// A vector drawing for state that doesn't fit a grid, e.g. polygons or projected points.
+/// The y axis points down, as in the puzzle inputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub shapes: Vec<Shape>,
+    pub caption: String,
+}
+
+impl Scene {
+    pub fn caption(self, caption: impl Into<String>) -> Self {
+        Self {
+            caption: caption.into(),
+            ..self
+        }
+    }
+
+    /// The smallest box `(min, max)` containing all shapes, or `None` for an empty scene.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut points = self.shapes.iter().flat_map(|shape| match shape {
+            Shape::Polygon { points, .. } => points.clone(),
+            Shape::Line { from, to, .. } => vec![*from, *to],
+            Shape::Dot { at, .. } => vec![*at],
+        });
+        let first = points.next()?;
+        Some(points.fold((first, first), |(min, max), (x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        }))
+    }
+}
+
+impl FromIterator<Shape> for Scene {
+    fn from_iter<I: IntoIterator<Item = Shape>>(iter: I) -> Self {
+        Self {
+            shapes: iter.into_iter().collect(),
+            caption: String::new(),
+        }
+    }
+}
+
+/// Receives the frames and scenes a solution produces while it is attached with [`attach`].
+pub trait Viewer {
+    fn show(&mut self, frame: &Frame);
+
+    /// Viewers that can only show grids ignore scenes.
+    fn draw(&mut self, _scene: &Scene) {}
+
+    /// Called once when the viewer is detached.
+    fn finish(&mut self) {}
+}
+
+/// Sends everything to each of the viewers, e.g. to watch and export a run at once.
+impl Viewer for Vec<Box<dyn Viewer>> {
+    fn show(&mut self, frame: &Frame) {
+        self.iter_mut().for_each(|viewer| viewer.show(frame));
+    }
+
+    fn draw(&mut self, scene: &Scene) {
+        self.iter_mut().for_each(|viewer| viewer.draw(scene));
+    }
+
+    fn finish(&mut self) {
+        self.iter_mut().for_each(|viewer| viewer.finish());
+    }
+}
+
+/// Plays frames as an animation on stderr, redrawing in place, so that stdout keeps only results.
+#[derive(Clone, Debug)]
+pub struct Terminal {
+    delay: Duration,
+    frames: usize,
+}
+
+impl Terminal {
+    /// A terminal viewer that waits `delay` after each frame.
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, frames: 0 }
+    }
+}
+
+impl Viewer for Terminal {
+    fn show(&mut self, frame: &Frame) {
+        // Clear the screen once and hide the cursor, then redraw from the top left.
+        let prefix = if self.frames == 0 {
+            "\x1b[?25l\x1b[2J\x1b[H"
+        } else {
+            "\x1b[H"
+        };
+        let _ = write!(std::io::stderr(), "{prefix}{}\x1b[J", frame.to_ansi());
+        self.frames += 1;
+        std::thread::sleep(self.delay);
+    }
+
+    fn finish(&mut self) {
+        let _ = write!(std::io::stderr(), "\x1b[?25h");
+    }
+}
+
+#[cfg(feature = "viz")]
+thread_local! {
+    static VIEWER: std::cell::RefCell<Option<Box<dyn Viewer>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Sends the frames produced on this thread to `viewer`, replacing any previous viewer.
+#[cfg(feature = "viz")]
+pub fn attach(viewer: Box<dyn Viewer>) {
+    VIEWER.with(|v| *v.borrow_mut() = Some(viewer));
+}
+
+/// Stops sending frames and lets the viewer finish.
+#[cfg(feature = "viz")]
+pub fn detach() {
+    if let Some(mut viewer) = VIEWER.with(|v| v.borrow_mut().take()) {
+        viewer.finish();
+    }
+}
+
+/// Whether frames are being shown. Use it to skip work that only serves the visualisation.
+#[cfg(feature = "viz")]
+pub fn is_active() -> bool {
+    VIEWER.with(|v| v.borrow().is_some())
+}
+
+/// Shows the frame built by `build`, which is only called while a viewer is attached.
+#[cfg(feature = "viz")]
+pub fn frame(build: impl FnOnce() -> Frame) {
+    if is_active() {
+        let frame = build();
+        VIEWER.with(|v| {
+            if let Some(viewer) = v.borrow_mut().as_mut() {
+                viewer.show(&frame);
+            }
+        });
+    }
+}
+
+/// Draws the scene built by `build`, which is only called while a viewer is attached.
+#[cfg(feature = "viz")]
+pub fn scene(build: impl FnOnce() -> Scene) {
+    if is_active() {
+        let scene = build();
+        VIEWER.with(|v| {
+            if let Some(viewer) = v.borrow_mut().as_mut() {
+                viewer.draw(&scene);
+            }
+        });
+    }
+}
+
+// Without the `viz` feature, nothing is ever attached and frames are never built.
+
+#[cfg(not(feature = "viz"))]
+pub fn attach(_viewer: Box<dyn Viewer>) {}
+
+#[cfg(not(feature = "viz"))]
+pub fn detach() {}
+
+#[cfg(not(feature = "viz"))]
+#[inline(always)]
+pub fn is_active() -> bool {
+    false
+}
+
+#[cfg(not(feature = "viz"))]
+#[inline(always)]
+pub fn frame(_build: impl FnOnce() -> Frame) {}
+
+#[cfg(not(feature = "viz"))]
+#[inline(always)]
+pub fn scene(_build: impl FnOnce() -> Scene) {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, Color, Frame, frame, is_active};
+    use crate::grid::Grid;
+
+    #[test]
+    fn renders_ansi_colours() {
+        let grid = Grid::parse("#.\n.#", |c| c == '#');
+        let frame = Frame::from_grid(&grid, |_, &on| {
+            if on {
+                Cell::new('#').fg(Color::Rgb(1, 2, 3))
+            } else {
+                Cell::new('.')
+            }
+        })
+        .caption("step 1");
+
+        assert_eq!(
+            frame.to_ansi(),
+            "\x1b[0m\x1b[38;2;1;2;3m#\x1b[0m.\n.\x1b[0m\x1b[38;2;1;2;3m#\x1b[0m\nstep 1\n"
+        );
+    }
+
+    #[test]
+    fn builds_frames_only_while_attached() {
+        assert!(!is_active());
+        frame(|| unreachable!("no viewer is attached"));
+    }
+
+    #[cfg(feature = "viz")]
+    #[test]
+    fn sends_frames_to_the_attached_viewer() {
+        use super::{Viewer, attach, detach};
+        use std::sync::{Arc, Mutex};
+
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Viewer for Recorder {
+            fn show(&mut self, frame: &Frame) {
+                self.0.lock().unwrap().push(frame.caption.clone());
+            }
+
+            fn finish(&mut self) {
+                self.0.lock().unwrap().push("done".into());
+            }
+        }
+
+        let captions = Arc::new(Mutex::new(Vec::new()));
+        attach(Box::new(Recorder(captions.clone())));
+        for i in 0..3 {
+            frame(|| {
+                Frame::from_grid(&Grid::filled(1, 1, ()), |_, _| Cell::new('x'))
+                    .caption(i.to_string())
+            });
+        }
+        detach();
+        frame(|| unreachable!("the viewer was detached"));
+
+        assert_eq!(*captions.lock().unwrap(), vec!["0", "1", "2", "done"]);
+    }
+}